@@ -15,6 +15,7 @@ main() {
     esac
 
     cargo check --target $TARGET
+    cargo check --target $TARGET --features hal
     cargo check --target $TARGET --examples
 }
 
@@ -7,12 +7,6 @@
 // Value where the potentiometer value indicates state-change
 const ADC_LIMIT: u16 = 800;
 
-// BIAS Pins
-const POT_BIAS_PIN: u32 = 8;
-
-// ADC PIN 0
-const FROM_POT_PIN: u8 = 0;
-
 extern crate panic_abort;
 
 extern crate cortex_m;
@@ -27,7 +21,6 @@ use rt::ExceptionFrame;
 use core::fmt::Write;
 use cortex_m_semihosting::hio;
 use lpc::lpc1347;
-use lpc::lpc1347::GPIO_PORT;
 use rtfm::{app, wfi, Threshold};
 
 // This is the resource, not the type
@@ -35,8 +28,9 @@ use lpc::lpc1347::CT16B0 as CT16B0_RES;
 use lpc::lpc1347::ADC;
 
 use lpc::adc;
+use lpc::clock;
 use lpc::gpio;
-use lpc::gpio::Port::Port0;
+use lpc::gpio::{Analog, OpenDrain, Output, PushPull};
 use lpc::timers16;
 use lpc::timers16::{MatchReg, Timer};
 
@@ -64,16 +58,18 @@ app! {
     device: lpc1347,
 
     resources: {
-        static GPIO_PORT: GPIO_PORT;
         static CT16B0_RES: CT16B0_RES;
         static ADC: ADC;
+        static POT: gpio::Pio0_11<Analog>;
+        static POT_BIAS: gpio::Pio0_8<Output<PushPull>>;
+        static LED: gpio::Pio0_4<Output<OpenDrain>>;
     },
 
     tasks: {
         CT16B0: {
             path: clock0_tick,
             priority: 1,
-            resources: [ADC, GPIO_PORT, CT16B0_RES],
+            resources: [ADC, POT, POT_BIAS, LED, CT16B0_RES],
         },
     }
 }
@@ -89,61 +85,45 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
         .sysahbclkctrl
         .modify(|_, w| w.iocon().enable());
 
-    // Configure ADC to use pio0_11 for power on/off
+    gpio::init(&p.device.SYSCON, false, false);
+    let pins = gpio::split(p.device.GPIO_PORT);
+
+    // Configure ADC to use pio0_11 (AD0) for power on/off
     adc::init(
         &p.device.SYSCON,
         &p.device.ADC,
-        FROM_POT_PIN,
-        24000000u32,
         true,
         false,
         adc::Capture::Rising,
-    );
+    ).unwrap();
     // POT
-    adc::set_adc_pin(&p.device.IOCON, adc::PinPos::Pin0);
-
-    gpio::init(&p.device.SYSCON, false, false);
-    p.device.IOCON.pio0_3.modify(|_, w| w.func().pio0_3_());
-    // Special i2c-pins
-    p.device
-        .IOCON
-        .pio0_4
-        .modify(|_, w| w.i2cmode().standard_io_functio());
-    p.device
-        .IOCON
-        .pio0_5
-        .modify(|_, w| w.i2cmode().standard_io_functio());
-
-    // Initialize GPIO and set pio0_3 to output
-    gpio::set_dir(&p.device.GPIO_PORT, Port0, 2, true);
-    gpio::set_dir(&p.device.GPIO_PORT, Port0, 3, true);
-    gpio::set_dir(&p.device.GPIO_PORT, Port0, 4, true);
-    gpio::set_dir(&p.device.GPIO_PORT, Port0, 5, true);
-    gpio::set_dir(&p.device.GPIO_PORT, Port0, 6, true);
-    gpio::set_dir(&p.device.GPIO_PORT, Port0, 9, true);
+    let pot = pins.pio0_11.into_analog();
 
     // POT_BIAS (pin 8)
-    gpio::set_dir(&p.device.GPIO_PORT, Port0, POT_BIAS_PIN, true);
-    gpio::set_pin_value(&p.device.GPIO_PORT, Port0, POT_BIAS_PIN, false);
+    let mut pot_bias = pins.pio0_8.into_output();
+    pot_bias.set_value(false);
 
     // LEDS
-    gpio::set_pin_value(&p.device.GPIO_PORT, Port0, 3, true);
-    gpio::set_pin_value(&p.device.GPIO_PORT, Port0, 4, true);
-    gpio::set_pin_value(&p.device.GPIO_PORT, Port0, 5, true);
-
-    gpio::set_pin_value(&p.device.GPIO_PORT, Port0, 6, true);
-    gpio::set_pin_value(&p.device.GPIO_PORT, Port0, 8, false);
-    gpio::set_pin_value(&p.device.GPIO_PORT, Port0, 9, true);
+    pins.pio0_2.into_output();
+    pins.pio0_3.into_output().set_value(true);
+    let mut led = pins.pio0_4.into_open_drain_output();
+    led.set_value(true);
+    pins.pio0_5.into_open_drain_output().set_value(true);
+    pins.pio0_6.into_output().set_value(true);
+    pins.pio0_9.into_output().set_value(true);
 
     // Clock 0 setup
-    // 24MHz systemclock, prescale 24000 and count to 500
+    // Prescale the system clock to 10kHz and count to 5_000
     // 2Hz
     timers16::reset_t0(&p.device.CT16B0);
     timers16::init(&p.device.SYSCON, &mut p.core.NVIC, Timer::Timer0);
     timers16::set_interrupt_t0(&p.device.CT16B0, MatchReg::Reg0, true, true, false);
-    timers16::set_prescaler_t0(&p.device.CT16B0, 24_000);
+    timers16::set_prescaler_t0(
+        &p.device.CT16B0,
+        (clock::get_system_clock() / 10_000).saturating_sub(1) as u16,
+    );
     timers16::set_enabled_t0(&p.device.CT16B0, true);
-    timers16::set_match_t0(&p.device.CT16B0, MatchReg::Reg0, 500u16);
+    timers16::set_match_t0(&p.device.CT16B0, MatchReg::Reg0, 5_000u16);
 
     {
         let mut stdout = hio::hstdout().unwrap();
@@ -151,9 +131,11 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
     }
 
     init::LateResources {
-        GPIO_PORT: p.device.GPIO_PORT,
         CT16B0_RES: p.device.CT16B0,
         ADC: p.device.ADC,
+        POT: pot,
+        POT_BIAS: pot_bias,
+        LED: led,
     }
 }
 
@@ -177,15 +159,23 @@ fn clock0_tick(_t: &mut Threshold, r: CT16B0::Resources) {
     // Check if the potentiometer is turned on
 
     // Activate POT_BIAS before
-    gpio::set_pin_value(&r.GPIO_PORT, Port0, POT_BIAS_PIN, true);
+    r.POT_BIAS.set_value(true);
 
     // Check if the POT is in OFF position
-    if adc::read(&r.ADC, FROM_POT_PIN) <= ADC_LIMIT {
-        gpio::set_pin_value(&r.GPIO_PORT, Port0, 4, false);
-    } else {
-        gpio::set_pin_value(&r.GPIO_PORT, Port0, 4, true);
+    let pot: &gpio::Pio0_11<Analog> = &r.POT;
+    match adc::read(&r.ADC, pot) {
+        Ok(value) if value > ADC_LIMIT => {
+            r.LED.set_value(true);
+        }
+        Ok(_) => {
+            r.LED.set_value(false);
+        }
+        Err(error) => {
+            let mut stdout = hio::hstdout().unwrap();
+            writeln!(stdout, "ADC error: {:?}", error).unwrap();
+        }
     }
 
     // Deactivate POT_BIAS
-    gpio::set_pin_value(&r.GPIO_PORT, Port0, POT_BIAS_PIN, false);
+    r.POT_BIAS.set_value(false);
 }
@@ -17,18 +17,15 @@ extern crate lpc1347_rtfm3 as lpc;
 extern crate cortex_m_rt as rt;
 use rt::ExceptionFrame;
 
-use rtfm::{app, Threshold, wfi};
+use rtfm::{app, Threshold};
 use cortex_m_semihosting::hio;
 use core::fmt::Write;
 use lpc::lpc1347;
 use lpc::lpc1347::{GPIO_PORT};
 
-// This is the resource, not the type
-use lpc::lpc1347::CT16B0 as CT16B0_RES;
-
+use lpc::clock;
 use lpc::gpio;
-use lpc::timers16;
-use lpc::timers16::{Timer, MatchReg};
+use lpc::systick::{ClockSource, SysTick};
 
 // #TODO Manual start lang item
 #[start]
@@ -57,19 +54,15 @@ app! {
 
     resources: {
         static GPIO_PORT: GPIO_PORT;
-        static CT16B0_RES: CT16B0_RES;
+        static SYSTICK: SysTick;
     },
 
-    tasks: {
-        CT16B0: {
-            path: clock0_tick,
-            priority: 1,
-            resources: [GPIO_PORT, CT16B0_RES],
-        },
-    }
+    idle: {
+        resources: [GPIO_PORT, SYSTICK],
+    },
 }
 
-fn init(mut p: init::Peripherals) -> init::LateResources {
+fn init(p: init::Peripherals) -> init::LateResources {
     {
         let mut stdout = hio::hstdout().unwrap();
         writeln!(stdout, "Initializing...").unwrap();
@@ -84,16 +77,8 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
     gpio::set_dir(&p.device.GPIO_PORT, gpio::Port::Port0, 5, true);
     gpio::set_pin_value(&p.device.GPIO_PORT, gpio::Port::Port0, 5, true);
 
-
-    // Clock 0 setup
-    // 24MHz systemclock, prescale 24000 and count to 1000
-    // 1Hz blink
-    timers16::reset_t0(&p.device.CT16B0);
-    timers16::init(&p.device.SYSCON, &mut p.core.NVIC, Timer::Timer0);
-    timers16::set_interrupt_t0(&p.device.CT16B0, MatchReg::Reg0, true, true, false);
-    timers16::set_prescaler_t0(&p.device.CT16B0, 24_000);
-    timers16::set_enabled_t0(&p.device.CT16B0, true);
-    timers16::set_match_t0(&p.device.CT16B0,  MatchReg::Reg0, 1000u16);
+    // SysTick on the core clock, whatever the system clock is set to
+    let systick = SysTick::new(p.core.SYST, ClockSource::Core, clock::get_system_clock());
 
     {
         let mut stdout = hio::hstdout().unwrap();
@@ -102,24 +87,20 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
 
     init::LateResources {
         GPIO_PORT: p.device.GPIO_PORT,
-        CT16B0_RES: p.device.CT16B0
+        SYSTICK: systick,
     }
 }
 
-fn idle() -> ! {
+// 1Hz blink
+fn idle(_t: &mut Threshold, r: idle::Resources) -> ! {
     loop {
-        wfi();
-    }
-}
-
-fn clock0_tick(_t: &mut Threshold, r: CT16B0::Resources) {
-
-    timers16::clear_interrupt_t0(&r.CT16B0_RES, MatchReg::Reg0);
+        r.SYSTICK.delay_ms(500);
 
-    let mut stdout = hio::hstdout().unwrap();
-    writeln!(stdout, "Clock 0!").unwrap();
+        let mut stdout = hio::hstdout().unwrap();
+        writeln!(stdout, "Tick!").unwrap();
 
-    r.GPIO_PORT.not[0].write(|w| w.notp3().bit(true));
-    r.GPIO_PORT.not[0].write(|w| w.notp4().bit(true));
-    r.GPIO_PORT.not[0].write(|w| w.notp5().bit(true));
+        r.GPIO_PORT.not[0].write(|w| w.notp3().bit(true));
+        r.GPIO_PORT.not[0].write(|w| w.notp4().bit(true));
+        r.GPIO_PORT.not[0].write(|w| w.notp5().bit(true));
+    }
 }
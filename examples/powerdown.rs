@@ -25,7 +25,6 @@ use rtfm::{app, wfi, Threshold};
 
 use lpc::clock;
 use lpc::gpio;
-use lpc::gpio::Port::Port0;
 use lpc::power;
 use lpc::timers16;
 use lpc::timers16::{MatchReg, Timer};
@@ -89,7 +88,7 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
         lpc1347::wwdt::clksel::CLKSELW::WATCHDOG_OSCILLATOR_,
         lpc1347::syscon::wdtoscctrl::FREQSELW::_0_6_MHZ,
         64,
-    );
+    ).unwrap();
 
     clock::wwdt_configure(
         &p.device.WWDT,
@@ -98,7 +97,7 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
         true,
         0xFF_FF_FF, // 9375+1023, should give 1 second
         1023,
-    );
+    ).unwrap();
 
     // Disable i2c clock
     p.device
@@ -106,46 +105,6 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
         .sysahbclkctrl
         .modify(|_, w| w.i2c().disable());
 
-    macro_rules! set_pulldown {
-        ($field:ident) => {{
-            p.device
-                .IOCON
-                .$field
-                .modify(|_, w| w.mode().pull_down_resistor_e());
-        }};
-    }
-    macro_rules! set_pullup {
-        ($field:expr) => {{
-            p.device
-                .IOCON
-                .pio0_$field
-                .modify(|_, w| w.mode().pull_up_resistor_ena());
-        }};
-    }
-    macro_rules! set_nopull {
-        ($field:ident) => {{
-            p.device
-                .IOCON
-                .$field
-                .modify(|_, w| w.mode().inactive_no_pull_do());
-        }};
-    }
-    macro_rules! set_gpio_output {
-        ($field:ident) => {{
-            gpio::set_dir(&p.device.GPIO_PORT, Port0, $field, true);
-        }};
-    }
-    macro_rules! set_gpio_input {
-        ($field:ident) => {{
-            gpio::set_dir(&p.device.GPIO_PORT, Port0, $field, false);
-        }};
-    }
-    macro_rules! set_gpio_low {
-        ($field:ident) => {{
-            gpio::set_pin_value(&p.device.GPIO_PORT, Port0, $field, false);
-        }};
-    }
-
     //Setup how a wfi should behave
     //power::sleep(&p.device.PMU, &mut p.core.SCB);
 
@@ -183,38 +142,17 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
 
     gpio::init(&p.device.SYSCON, false, false);
 
-    // Set inputs according to product datasheet footnote on page 42
+    // Park the pins according to product datasheet footnote on page 42
     //   "[5]
     //   IDD measurements were performed with all pins configured as GPIO outputs driven LOW and
     //   pull-up resistors disabled."
-    set_nopull!(reset_pio0_0);
-    set_nopull!(pio0_1);
-    set_nopull!(pio0_2);
-    set_nopull!(pio0_3);
-    // 4 and 5 special i2c pins
-    set_nopull!(pio0_6);
-    set_nopull!(pio0_7);
-    set_nopull!(pio0_8);
-    set_nopull!(pio0_9);
-    set_nopull!(swclk_pio0_10);
-    set_nopull!(tdi_pio0_11);
-    set_nopull!(tms_pio0_12);
-    set_nopull!(tdo_pio0_13);
-    set_nopull!(trst_pio0_14);
-    set_nopull!(swdio_pio0_15);
-    set_nopull!(pio0_16);
-    set_nopull!(pio0_17);
-    set_nopull!(pio0_18);
-    set_nopull!(pio0_19);
-    set_nopull!(pio0_20);
-    set_nopull!(pio0_21);
-    set_nopull!(pio0_22);
-    set_nopull!(pio0_23);
-
-    for x in 0..23 {
-        set_gpio_output!(x);
-        set_gpio_low!(x);
-    }
+    // The reset and SWD pins are kept, so that the board can still be flashed.
+    // The pins are never restored since the example only ever sleeps.
+    power::park_pins(
+        &p.device.IOCON,
+        &p.device.GPIO_PORT,
+        power::KeepPins::new().debug(),
+    );
 
     init::LateResources {
         GPIO_PORT: p.device.GPIO_PORT,
@@ -107,7 +107,7 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
         lpc1347::wwdt::clksel::CLKSELW::WATCHDOG_OSCILLATOR_,
         lpc1347::syscon::wdtoscctrl::FREQSELW::_0_6_MHZ,
         64,
-    );
+    ).unwrap();
 
     clock::wwdt_configure(
         &p.device.WWDT,
@@ -116,17 +116,20 @@ fn init(mut p: init::Peripherals) -> init::LateResources {
         true,
         9375, // 9375+1023, should give 1 second
         1023,
-    );
+    ).unwrap();
 
     // Clock 0 setup
-    // 24MHz systemclock, prescale 24000 and count to 1000
+    // Prescale the system clock to 10kHz and count to 10_000
     // 1Hz blink
     timers16::reset_t0(&p.device.CT16B0);
     timers16::init(&p.device.SYSCON, &mut p.core.NVIC, Timer::Timer0);
     timers16::set_interrupt_t0(&p.device.CT16B0, MatchReg::Reg0, true, true, false);
-    timers16::set_prescaler_t0(&p.device.CT16B0, 24_000);
+    timers16::set_prescaler_t0(
+        &p.device.CT16B0,
+        (clock::get_system_clock() / 10_000).saturating_sub(1) as u16,
+    );
     timers16::set_enabled_t0(&p.device.CT16B0, true);
-    timers16::set_match_t0(&p.device.CT16B0, MatchReg::Reg0, 1000u16);
+    timers16::set_match_t0(&p.device.CT16B0, MatchReg::Reg0, 10_000u16);
 
     {
         let mut stdout = hio::hstdout().unwrap();
@@ -1,25 +1,25 @@
 #![allow(dead_code)]
 extern crate lpc1347;
+use clock;
+use error::Error;
+use lpc1347::Interrupt;
+use timer::{Control, Instance, MatchReg, Timer};
 
-/// Maps ADC channels to pins
-#[derive(Copy, Clone)]
-pub enum PinPos {
-    /// Channel 0 => pio0_11
-    Pin0 = 11,
-    /// Channel 1 => pio0_12
-    Pin1 = 12,
-    /// Channel 2 => pio0_13
-    Pin2 = 13,
-    /// Channel 3 => pio0_15
-    Pin3 = 14,
-    /// Channel 4 => pio0_15
-    Pin4 = 15,
-    /// Channel 5 => pio0_16
-    Pin5 = 16,
-    /// Channel 6 => pio0_22
-    Pin6 = 22,
-    /// Channel 7 => pio0_23
-    Pin7 = 23,
+/// Keeps `Channel` from being implemented outside this module
+mod sealed {
+    /// Supertrait of `Channel` that cannot be named by other crates
+    pub trait Sealed {}
+}
+
+/// An analog pin and the A/D channel that it is connected to
+///
+/// Implemented for the pins of `gpio` in `Analog` mode, which are obtained
+/// through `into_analog`. Only such pins can be passed to `read`, so the pin
+/// is always routed to the ADC before it is sampled. The trait is sealed, so
+/// the channel is always one of the eight of the ADC.
+pub trait Channel: sealed::Sealed {
+    /// The A/D channel (0-7)
+    const CHANNEL: u8;
 }
 
 /// Specify event for capture triggers
@@ -31,33 +31,92 @@ pub enum Capture {
     Falling,
 }
 
+/// Hardware events that can start a conversion
+#[derive(Copy, Clone)]
+pub enum Start {
+    /// An edge on pio0_2
+    Pio0_2 = 0x2,
+    /// An edge on pio1_5
+    Pio1_5 = 0x3,
+    /// An edge on the CT32B0_MAT0 output
+    Ct32b0Mat0 = 0x4,
+    /// An edge on the CT32B0_MAT1 output
+    Ct32b0Mat1 = 0x5,
+    /// An edge on the CT16B0_MAT0 output
+    Ct16b0Mat0 = 0x6,
+    /// An edge on the CT16B0_MAT1 output
+    Ct16b0Mat1 = 0x7,
+}
+
+/// A timer whose match outputs can start conversions, CT16B0 or CT32B0
+pub trait TriggerTimer: Instance {
+    /// Start event of MAT0, MAT1 follows it
+    const START: Start;
+}
+
+impl TriggerTimer for lpc1347::CT16B0 {
+    const START: Start = Start::Ct16b0Mat0;
+}
+
+impl TriggerTimer for lpc1347::CT32B0 {
+    const START: Start = Start::Ct32b0Mat0;
+}
+
+/// Start field of CR
+const CR_START: u32 = 0x7 << 24;
+
+/// Edge bit of CR
+const CR_EDGE: u32 = 1 << 27;
+
+/// Global interrupt enable bit of INTEN
+const INTEN_ADGINTEN: u32 = 1 << 8;
+
+/// Overrun bit of GDR
+const GDR_OVERRUN: u32 = 1 << 30;
+
+/// Done bit of GDR
+const GDR_DONE: u32 = 1 << 31;
+
+/// Maximum ADC clock in 12-bit mode
+const MAX_CLOCK_12BIT: u32 = 15_500_000;
+
+/// Maximum ADC clock in 10-bit mode
+const MAX_CLOCK_10BIT: u32 = 31_000_000;
+
+/// ADC clocks per conversion
+const CONVERSION_CLOCKS: u32 = 31;
+
+/// Polls of `read` before it gives up
+const READ_TIMEOUT: u32 = 0x1_0000;
+
 /// Initialize the ADC
 ///
+/// The ADC clock is derived from `clock::get_system_clock`, using the fastest
+/// divider within the limit of the conversion mode. Returns the conversion
+/// rate in samples per second, or `Error::InvalidDivider` if the system clock
+/// is disabled.
+///
 /// # Arguments
-/// * `pinnum` - The AD-pin to use (0-7)
-/// * `system_core_clock` - Clock frequency for the main clock
 /// * `low_power` - Use less power
 /// * `mode10bit` - Limit sampling to 10bit to allow 31MHz sampling
 /// * `edge` - Set interrupts on rising or falling edges
 ///
 /// # Example
 /// ```
-/// // Configure ADC to read from pin 5
-/// adc::init(&p.SYSCON, &p.ADC, 5u8, 24000000u32, false, false, Capture::Rising);
-/// adc::set_adc_pin(&p.IOCON, adc::PinPos::Pin5);
+/// // Configure ADC to read from AD5 (pio0_16)
+/// let rate = adc::init(&p.device.SYSCON, &p.device.ADC, false, false, Capture::Rising).unwrap();
+/// let pot = pins.pio0_16.into_analog();
 /// ```
 pub fn init(
     syscon: &lpc1347::SYSCON,
     adc: &lpc1347::ADC,
-    pinnum: u8,
-    system_core_clock: u32,
     low_power: bool,
     mode10bit: bool,
     edge: Capture,
-) {
-    // Disallow invalid pins
-    if pinnum > 7 {
-        panic!("invalid pin number initialized");
+) -> Result<u32, Error> {
+    let system_clock = clock::get_system_clock();
+    if system_clock == 0 {
+        return Err(Error::InvalidDivider);
     }
 
     // Power up ADC module
@@ -67,24 +126,18 @@ pub fn init(
     // Stop the ADC
     adc.cr.modify(|_, w| w.start().no_start_this_value());
 
-    unsafe {
-        // Select channels
-        adc.cr
-            .modify(|r, w| w.sel().bits(r.sel().bits() | 1 << pinnum));
-
-        // Read the system clock divider
-        // to be able to calculate the clockdiv for ADC
-        // The quicker the better, requires about 31 cycles to complete
-        // Maximum frequency for 12 bit, 15.5MHz, 10 bit 31MHz
-
-        let clkdiv = u32::from(syscon.sysahbclkdiv.read().div().bits());
+    // The quicker the better, a conversion takes 31 cycles
+    let max_clock = if mode10bit {
+        MAX_CLOCK_10BIT
+    } else {
+        MAX_CLOCK_12BIT
+    };
+    let clkdiv = ::core::cmp::min((system_clock - 1) / max_clock, 0xFF);
 
+    unsafe {
         // Set ADC clock divider
-        // ADC is driven on the APB bus
-        adc.cr.modify(|_, w| {
-            w.clkdiv()
-                .bits(((system_core_clock / (system_core_clock / clkdiv)) - 1) as u8)
-        });
+        // ADC is driven by the system clock, divided by CLKDIV + 1
+        adc.cr.modify(|_, w| w.clkdiv().bits(clkdiv as u8));
     }
 
     // Set software control
@@ -105,110 +158,528 @@ pub fn init(
             adc.cr.modify(|_, w| w.edge().falling());
         }
     }
+
+    Ok(system_clock / (clkdiv + 1) / CONVERSION_CLOCKS)
 }
 
-/// Configure the board to read from pin ADn
+/// Read from the ADC at the channel of an analog pin
+///
+/// Gives up with `Error::Timeout` if the conversion does not finish, e.g.
+/// because the ADC is not clocked.
 ///
 /// # Arguments
-/// * `pin` - The AD-pin to use (0-7)
-pub fn set_adc_pin(iocon: &lpc1347::IOCON, pin: PinPos) {
-    match pin {
-        PinPos::Pin0 => {
-            iocon
-                .tdi_pio0_11
-                .modify(|_, w| w.admode().analog_input_mode_());
-            iocon.tdi_pio0_11.modify(|_, w| w.func().ad0_());
-        }
-        PinPos::Pin1 => {
-            iocon
-                .tms_pio0_12
-                .modify(|_, w| w.admode().analog_input_mode_());
-            iocon.tms_pio0_12.modify(|_, w| w.func().ad1_());
-        }
-        PinPos::Pin2 => {
-            iocon
-                .tdo_pio0_13
-                .modify(|_, w| w.admode().analog_input_mode_());
-            iocon.tdo_pio0_13.modify(|_, w| w.func().ad2_());
-        }
-        PinPos::Pin3 => {
-            iocon
-                .trst_pio0_14
-                .modify(|_, w| w.admode().analog_input_mode_());
-            iocon.trst_pio0_14.modify(|_, w| w.func().ad3_());
-        }
-        PinPos::Pin4 => {
-            iocon
-                .swdio_pio0_15
-                .modify(|_, w| w.admode().analog_input_mode_());
-            iocon.swdio_pio0_15.modify(|_, w| w.func().ad4_());
-        }
-        PinPos::Pin5 => {
-            iocon.pio0_16.modify(|_, w| w.admode().analog_input_mode_());
-            iocon.pio0_16.modify(|_, w| w.func().ad5_());
-        }
-        PinPos::Pin6 => {
-            iocon.pio0_22.modify(|_, w| w.admode().analog_input_mode_());
-            iocon.pio0_22.modify(|_, w| w.func().ad6_());
-        }
-        PinPos::Pin7 => {
-            iocon.pio0_23.modify(|_, w| w.admode().analog_input_mode_());
-            iocon.pio0_23.modify(|_, w| w.func().ad7_());
+/// * `pin` - The pin to sample, converted with `into_analog`
+///
+/// # Example
+/// ```
+/// let pot = pins.pio0_11.into_analog();
+/// if let Ok(value) = adc::read(&p.device.ADC, &pot) {
+///     let mut stdout = hio::hstdout().unwrap();
+///     let _ = writeln!(stdout, "ADC ({})", value);
+/// }
+/// ```
+pub fn read<C: Channel>(adc: &lpc1347::ADC, pin: &C) -> Result<u16, Error> {
+    start(adc, pin);
+
+    // A conversion takes at most 31 ADC clocks of 256 system clocks each
+    for _ in 0..READ_TIMEOUT {
+        match poll(adc) {
+            Ok(value) => return Ok(value),
+            Err(::nb::Error::Other(error)) => return Err(error),
+            Err(::nb::Error::WouldBlock) => {}
         }
     }
+
+    // Stop conversion
+    adc.cr.modify(|_, w| w.start().no_start_this_value());
+    Err(Error::Timeout)
 }
 
-/// Read from the ADC at some channel
+/// Start a conversion at the channel of an analog pin, without waiting for it
+///
+/// The result is returned by `poll`, or, after `listen`, handed to the `ADC`
+/// task through `on_interrupt`.
 ///
 /// # Arguments
-/// * `channel` - A/D channel to sample
+/// * `pin` - The pin to sample, converted with `into_analog`
 ///
 /// # Example
 /// ```
-/// {
-///     let mut stdout = hio::hstdout().unwrap();
-///     let _ = writeln!(stdout, "ADC ({})", adc::read(r.ADC, 5));
-/// }
+/// adc::start(&r.ADC, &pot);
+/// // ...
+/// let value = block!(adc::poll(&r.ADC)).unwrap();
 /// ```
-pub fn read(adc: &lpc1347::ADC, channel: u8) -> u16 {
-    if channel > 7 {
-        panic!("invalid channel selected")
-    }
+pub fn start<C: Channel>(adc: &lpc1347::ADC, _pin: &C) {
+    start_channel(adc, C::CHANNEL);
+}
+
+/// Start a conversion on a channel
+fn start_channel(adc: &lpc1347::ADC, channel: u8) {
+    // Discard an old result, its DONE bit would end `poll` right away
+    let _ = adc.dr[channel as usize].read();
 
     // Start read on channel
     unsafe {
         adc.cr.modify(|_, w| w.sel().bits(1 << channel));
     }
     adc.cr.modify(|_, w| w.start().start_conversion_now());
+}
 
-    // Read data
-    let mut register_value;
-    loop {
-        match channel {
-            0 => register_value = adc.dr[0].read(),
-            1 => register_value = adc.dr[1].read(),
-            2 => register_value = adc.dr[2].read(),
-            3 => register_value = adc.dr[3].read(),
-            4 => register_value = adc.dr[4].read(),
-            5 => register_value = adc.dr[5].read(),
-            6 => register_value = adc.dr[6].read(),
-            7 => register_value = adc.dr[7].read(),
-            _ => panic!("invalid channel selected!"),
-        }
+/// Get the result of the conversion started by `start`
+///
+/// Returns `nb::Error::WouldBlock` while the conversion is running, and
+/// `Error::InvalidChannel` if no channel is selected.
+pub fn poll(adc: &lpc1347::ADC) -> ::nb::Result<u16, Error> {
+    let channels = adc.cr.read().sel().bits();
+    if channels == 0 {
+        return Err(::nb::Error::Other(Error::InvalidChannel));
+    }
 
-        if register_value.done().bit_is_set() {
-            break;
+    match result(adc, channels.trailing_zeros() as u8) {
+        Some(value) => {
+            // Stop conversion
+            adc.cr.modify(|_, w| w.start().no_start_this_value());
+            Ok(value)
         }
+        None => Err(::nb::Error::WouldBlock),
+    }
+}
+
+/// Interrupt when a conversion started by `start` is done
+pub fn listen(adc: &lpc1347::ADC, nvic: &mut lpc1347::NVIC) {
+    unsafe {
+        adc.inten.write(|w| w.bits(INTEN_ADGINTEN));
+    }
+    nvic.enable(Interrupt::ADC);
+}
+
+/// Stop interrupting when a conversion is done
+pub fn unlisten(adc: &lpc1347::ADC, nvic: &mut lpc1347::NVIC) {
+    unsafe {
+        adc.inten.write(|w| w.bits(0));
+    }
+    nvic.disable(Interrupt::ADC);
+}
+
+/// Store the result of a conversion, call this from the `ADC` task
+///
+/// The result lands in `scan`, which flags an overrun if an earlier result was
+/// lost. Returns false if no conversion was done. Reading the result clears
+/// the interrupt.
+///
+/// # Example
+/// ```
+/// fn adc_done(_t: &mut Threshold, mut r: ADC::Resources) {
+///     // POT is a `gpio::Pio0_11<Analog>`
+///     let pot: &gpio::Pio0_11<Analog> = &r.POT;
+///     if adc::on_interrupt(&r.ADC, &mut r.SCAN) {
+///         let value = r.SCAN.take(pot);
+///         // Start the next one
+///         adc::start(&r.ADC, pot);
+///     }
+/// }
+/// ```
+pub fn on_interrupt(adc: &lpc1347::ADC, scan: &mut Scan) -> bool {
+    let bits = adc.gdr.read().bits();
+    if bits & GDR_DONE == 0 {
+        return false;
     }
 
     // Stop conversion
     adc.cr.modify(|_, w| w.start().no_start_this_value());
 
-    // Return value, depends on 10-bit mode
+    let channel = ((bits >> 24) & 0x7) as u8;
+    let value = scale(adc, ((bits >> 4) & 0xFFF) as u16);
+    scan.store(channel, value, bits & GDR_OVERRUN != 0);
+
+    true
+}
+
+/// Start a conversion at the channel of an analog pin whenever a hardware
+/// event occurs
+///
+/// The ADC must be set up through `init` first. When `interrupt` is set, the
+/// `ADC` interrupt fires once the conversion is done, see `get_result`.
+///
+/// # Arguments
+/// * `pin` - The pin to sample, converted with `into_analog`
+/// * `start` - The event that starts a conversion
+/// * `edge` - The edge of the event
+/// * `interrupt` - Interrupt when a conversion is done
+pub fn set_trigger<C: Channel>(
+    adc: &lpc1347::ADC,
+    nvic: &mut lpc1347::NVIC,
+    _pin: &C,
+    start: Start,
+    edge: Capture,
+    interrupt: bool,
+) {
+    let channel = C::CHANNEL;
+    let edge = match edge {
+        Capture::Rising => 0,
+        Capture::Falling => CR_EDGE,
+    };
+
+    unsafe {
+        // Software controlled mode converts the one selected channel per event
+        adc.cr.modify(|_, w| w.burst().software_controlled_());
+        adc.cr.modify(|r, w| {
+            let bits = r.bits() & !(0xFF | CR_START | CR_EDGE);
+            w.bits(bits | 1 << channel | (start as u32) << 24 | edge)
+        });
+        adc.inten
+            .write(|w| w.bits(if interrupt { 1 << channel } else { 0 }));
+    }
+
+    if interrupt {
+        nvic.enable(Interrupt::ADC);
+    } else {
+        nvic.disable(Interrupt::ADC);
+    }
+}
+
+/// Sample an analog pin at a fixed rate, started by a timer match output
+///
+/// The match register toggles its output and resets the counter, so the
+/// timer runs at twice the sample rate and every rising edge starts a
+/// conversion. This is free of task latency, and the timer is started.
+///
+/// # Arguments
+/// * `pin` - The pin to sample, converted with `into_analog`
+/// * `timer` - CT16B0 or CT32B0
+/// * `mr` - MR0 or MR1, `Error::InvalidTrigger` otherwise
+/// * `rate` - The sample rate in Hz, `Error::InvalidFrequency` if the timer
+///   cannot run at twice that
+/// * `pclk` - The peripheral clock of the timer in Hz
+///
+/// # Example
+/// ```
+/// // Sample AD5 at 8 kHz, interrupting after every conversion
+/// adc::init(&p.device.SYSCON, &p.device.ADC, false, false, Capture::Rising).unwrap();
+/// let mic = pins.pio0_16.into_analog();
+///
+/// let mut timer = Timer::new(p.device.CT32B0, &p.device.SYSCON);
+/// adc::set_timer_trigger(&p.device.ADC, &mut p.core.NVIC, &mic, &mut timer, MatchReg::Reg0,
+///                        8_000, clock::get_system_clock()).unwrap();
+/// ```
+pub fn set_timer_trigger<C: Channel, T: TriggerTimer>(
+    adc: &lpc1347::ADC,
+    nvic: &mut lpc1347::NVIC,
+    pin: &C,
+    timer: &mut Timer<T>,
+    mr: MatchReg,
+    rate: u32,
+    pclk: u32,
+) -> Result<(), Error> {
+    let start = match (T::START, mr) {
+        (Start::Ct16b0Mat0, MatchReg::Reg0) => Start::Ct16b0Mat0,
+        (Start::Ct16b0Mat0, MatchReg::Reg1) => Start::Ct16b0Mat1,
+        (Start::Ct32b0Mat0, MatchReg::Reg0) => Start::Ct32b0Mat0,
+        (Start::Ct32b0Mat0, MatchReg::Reg1) => Start::Ct32b0Mat1,
+        _ => return Err(Error::InvalidTrigger),
+    };
+    let frequency = rate.checked_mul(2).ok_or(Error::InvalidFrequency)?;
+
+    timer.set_enabled(false);
+    timer.reset();
+    timer.set_frequency(mr, frequency, pclk)?;
+    timer.set_external_match(mr, Control::Toggle, false);
+
+    set_trigger(adc, nvic, pin, start, Capture::Rising, true);
+    timer.set_enabled(true);
+
+    Ok(())
+}
+
+/// Stop hardware started conversions and disable the `ADC` interrupt
+pub fn clear_trigger(adc: &lpc1347::ADC, nvic: &mut lpc1347::NVIC) {
+    adc.cr.modify(|_, w| w.start().no_start_this_value());
+    unsafe {
+        adc.inten.write(|w| w.bits(0));
+    }
+    nvic.disable(Interrupt::ADC);
+}
+
+/// Get the result of a finished conversion, call this from the `ADC` task
+///
+/// Returns `None` if the channel of the pin has no new result. Reading the
+/// result clears the interrupt.
+///
+/// # Arguments
+/// * `pin` - The sampled pin, converted with `into_analog`
+pub fn get_result<C: Channel>(adc: &lpc1347::ADC, _pin: &C) -> Option<u16> {
+    result(adc, C::CHANNEL)
+}
+
+/// Get the new result of a channel
+fn result(adc: &lpc1347::ADC, channel: u8) -> Option<u16> {
+    let register_value = adc.dr[channel as usize].read();
+    if !register_value.done().bit_is_set() {
+        return None;
+    }
+
+    Some(scale(adc, register_value.v_vref().bits()))
+}
+
+/// Scale a raw result to the resolution of the conversion mode
+fn scale(adc: &lpc1347::ADC, value: u16) -> u16 {
     if adc.cr.read().mode10bit().is_enable_the_10_bit_co() {
         // In 10 bit mode, the two LSB bits are forced to 0, thus shift 2 steps
-        (register_value.v_vref().bits() >> 2) & 0x3FF
+        (value >> 2) & 0x3FF
     } else {
-        register_value.v_vref().bits() & 0xFFF
+        value & 0xFFF
+    }
+}
+
+/// Latest results of a burst scan, one per channel
+///
+/// Filled from the `ADC` task by `read_burst`, and consumed by other tasks
+/// through `take`.
+#[derive(Copy, Clone)]
+pub struct Scan {
+    values: [u16; 8],
+    converted: u8,
+    fresh: u8,
+    overrun: u8,
+}
+
+impl Scan {
+    /// Create an empty buffer
+    pub fn new() -> Self {
+        Scan {
+            values: [0; 8],
+            converted: 0,
+            fresh: 0,
+            overrun: 0,
+        }
+    }
+
+    /// Get the latest value of a pin, `None` if it has never been converted
+    pub fn get<C: Channel>(&self, _pin: &C) -> Option<u16> {
+        let channel = C::CHANNEL;
+        if self.converted & (1 << channel) == 0 {
+            return None;
+        }
+        Some(self.values[channel as usize])
+    }
+
+    /// Take a value of a pin that has not been taken before
+    pub fn take<C: Channel>(&mut self, _pin: &C) -> Option<u16> {
+        let channel = C::CHANNEL;
+        if self.fresh & (1 << channel) == 0 {
+            return None;
+        }
+        self.fresh &= !(1 << channel);
+        self.overrun &= !(1 << channel);
+        Some(self.values[channel as usize])
+    }
+
+    /// Determine if a result of the pin was overwritten before it was read
+    ///
+    /// The flag is cleared by `take`.
+    pub fn is_overrun<C: Channel>(&self, _pin: &C) -> bool {
+        self.overrun & (1 << C::CHANNEL) != 0
+    }
+
+    /// Store a result, `overrun` if the ADC lost one before it
+    fn store(&mut self, channel: u8, value: u16, overrun: bool) {
+        // Missed results, in the ADC or in the buffer
+        if overrun || self.fresh & (1 << channel) != 0 {
+            self.overrun |= 1 << channel;
+        }
+
+        self.values[channel as usize] = value;
+        self.converted |= 1 << channel;
+        self.fresh |= 1 << channel;
+    }
+}
+
+impl Default for Scan {
+    fn default() -> Self {
+        Scan::new()
     }
 }
+
+/// A set of analog pins to scan, see `start_burst`
+#[derive(Copy, Clone)]
+pub struct Channels {
+    mask: u8,
+}
+
+impl Channels {
+    /// Start an empty set
+    pub fn new() -> Self {
+        Channels { mask: 0 }
+    }
+
+    /// Add a pin, converted with `into_analog`
+    pub fn with<C: Channel>(mut self, _pin: &C) -> Self {
+        self.mask |= 1 << C::CHANNEL;
+        self
+    }
+}
+
+impl Default for Channels {
+    fn default() -> Self {
+        Channels::new()
+    }
+}
+
+/// Convert a set of analog pins over and over in burst mode
+///
+/// The `ADC` interrupt fires every time the highest channel of the set has
+/// been converted, i.e. once per scan. Call `read_burst` from the `ADC` task.
+///
+/// # Arguments
+/// * `channels` - The pins to scan, `Error::InvalidChannel` if there are none
+///
+/// # Example
+/// ```
+/// // Scan AD0, AD1 and AD5
+/// let x = pins.pio0_11.into_analog();
+/// let y = pins.pio0_12.into_analog();
+/// let z = pins.pio0_16.into_analog();
+/// let channels = adc::Channels::new().with(&x).with(&y).with(&z);
+/// adc::start_burst(&p.device.ADC, &mut p.core.NVIC, channels).unwrap();
+///
+/// // In the ADC task, which shares `SCAN` with the consumer
+/// adc::read_burst(&r.ADC, &mut r.SCAN);
+/// ```
+pub fn start_burst(
+    adc: &lpc1347::ADC,
+    nvic: &mut lpc1347::NVIC,
+    channels: Channels,
+) -> Result<(), Error> {
+    let channels = channels.mask;
+    if channels == 0 {
+        return Err(Error::InvalidChannel);
+    }
+
+    // The highest channel is converted last
+    let last = 7 - channels.leading_zeros();
+
+    unsafe {
+        // Burst mode requires the start field to be cleared
+        adc.cr
+            .modify(|r, w| w.bits(r.bits() & !(0xFF | CR_START) | u32::from(channels)));
+        adc.inten.write(|w| w.bits(1 << last));
+    }
+    adc.cr.modify(|_, w| w.burst().bit(true));
+    nvic.enable(Interrupt::ADC);
+
+    Ok(())
+}
+
+/// Stop burst mode and disable the `ADC` interrupt
+pub fn stop_burst(adc: &lpc1347::ADC, nvic: &mut lpc1347::NVIC) {
+    adc.cr.modify(|_, w| w.burst().software_controlled_());
+    unsafe {
+        adc.inten.write(|w| w.bits(0));
+    }
+    nvic.disable(Interrupt::ADC);
+}
+
+/// Store the results of a burst scan, call this from the `ADC` task
+///
+/// Reading the results clears the interrupt.
+pub fn read_burst(adc: &lpc1347::ADC, scan: &mut Scan) {
+    let channels = adc.cr.read().sel().bits();
+
+    for channel in 0..8 {
+        if channels & (1 << channel) == 0 {
+            continue;
+        }
+
+        let register_value = adc.dr[channel as usize].read();
+        if !register_value.done().bit_is_set() {
+            continue;
+        }
+
+        let value = scale(adc, register_value.v_vref().bits());
+        scan.store(channel, value, register_value.overrun().bit_is_set());
+    }
+}
+
+/// Owned ADC for use through the embedded-hal `OneShot` trait
+///
+/// The ADC must be set up through `init` first, channels are selected by
+/// passing a pin that has been converted with `into_analog`.
+///
+/// # Example
+/// ```
+/// adc::init(&p.device.SYSCON, &p.device.ADC, false, false, adc::Capture::Rising).unwrap();
+/// let mut pot = pins.pio0_11.into_analog();
+///
+/// let mut adc = adc::Adc::new(p.device.ADC);
+/// let value: u16 = block!(adc.read(&mut pot)).unwrap();
+/// ```
+#[cfg(feature = "hal")]
+pub struct Adc {
+    adc: lpc1347::ADC,
+    pending: Option<u8>,
+}
+
+#[cfg(feature = "hal")]
+impl Adc {
+    /// Take ownership of an initialized ADC
+    pub fn new(adc: lpc1347::ADC) -> Self {
+        Adc { adc, pending: None }
+    }
+
+    /// Release the ADC
+    pub fn free(self) -> lpc1347::ADC {
+        self.adc
+    }
+}
+
+#[cfg(feature = "hal")]
+impl<PIN> ::embedded_hal::adc::OneShot<Adc, u16, PIN> for Adc
+where
+    PIN: Channel + ::embedded_hal::adc::Channel<Adc, ID = u8>,
+{
+    type Error = Error;
+
+    fn read(&mut self, pin: &mut PIN) -> ::nb::Result<u16, Self::Error> {
+        if self.pending != Some(PIN::CHANNEL) {
+            start(&self.adc, pin);
+            self.pending = Some(PIN::CHANNEL);
+        }
+
+        let value = poll(&self.adc)?;
+        self.pending = None;
+        Ok(value)
+    }
+}
+
+/// Maps analog pins to their A/D channel
+macro_rules! channels {
+    ($($PIOX: ident: $channel: expr,)+) => {
+        $(
+            impl sealed::Sealed for ::gpio::$PIOX<::gpio::Analog> {}
+
+            impl Channel for ::gpio::$PIOX<::gpio::Analog> {
+                const CHANNEL: u8 = $channel;
+            }
+
+            #[cfg(feature = "hal")]
+            impl ::embedded_hal::adc::Channel<Adc> for ::gpio::$PIOX<::gpio::Analog> {
+                type ID = u8;
+
+                fn channel() -> u8 {
+                    <Self as Channel>::CHANNEL
+                }
+            }
+        )+
+    };
+}
+
+channels! {
+    Pio0_11: 0,
+    Pio0_12: 1,
+    Pio0_13: 2,
+    Pio0_14: 3,
+    Pio0_15: 4,
+    Pio0_16: 5,
+    Pio0_22: 6,
+    Pio0_23: 7,
+}
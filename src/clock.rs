@@ -1,13 +1,62 @@
 #![allow(dead_code)]
 extern crate lpc1347;
 
+use core::sync::atomic::{AtomicUsize, Ordering};
+use error::Error;
+
+/// Frequency of the internal RC oscillator, the main clock after reset
+pub const IRC_HZ: u32 = 12_000_000;
+
+/// Frequency of the crystal oscillator, as assumed by `pll_init`
+pub const CRYSTAL_HZ: u32 = 12_000_000;
+
+/// Frequencies of the watchdog oscillator by FREQSEL, in kHz
+const WDTOSC_KHZ: [u32; 16] = [
+    0, 600, 1050, 1400, 1750, 2100, 2400, 2700, 3000, 3250, 3500, 3750, 4000, 4200, 4400, 4600,
+];
+
+/// The main clock in Hz, as set through this module
+static MAIN_CLOCK: AtomicUsize = AtomicUsize::new(IRC_HZ as usize);
+
+/// The system clock divider, as set through this module
+static SYSTEM_DIVIDER: AtomicUsize = AtomicUsize::new(1);
+
+/// Get the frequency of the main clock in Hz
+///
+/// The frequency is tracked by `pll_init` and `wwdt_as_mainclk`, use
+/// `set_main_clock` after configuring the clocks by other means.
+pub fn get_main_clock() -> u32 {
+    MAIN_CLOCK.load(Ordering::Relaxed) as u32
+}
+
+/// Record the frequency of the main clock in Hz
+pub fn set_main_clock(frequency: u32) {
+    MAIN_CLOCK.store(frequency as usize, Ordering::Relaxed);
+}
+
+/// Get the frequency of the system clock in Hz, zero if it is disabled
+///
+/// This is the main clock divided by the divider of `sysclock_set_divider`,
+/// and it clocks the core and the peripherals. `adc::init` reads it itself;
+/// the drivers that take a clock argument (`ritimer::set_frequency`,
+/// `SysTick::new`, `Timer::set_frequency`, `Pwm::new`, `Monotonic::new` and
+/// `FrequencyMeter::new`) do not read it, pass them this value.
+pub fn get_system_clock() -> u32 {
+    match SYSTEM_DIVIDER.load(Ordering::Relaxed) as u32 {
+        0 => 0,
+        divider => get_main_clock() / divider,
+    }
+}
+
 /// Configure the phase-locked-loop
 /// Some values of m and p may make the MCU crash, see page 15 and 44 of UM10524
 /// for details.
 ///
+/// The main clock becomes `(mval + 1) * CRYSTAL_HZ`.
+///
 /// # Arguments
 /// * `mval` - Feedback divider value
-/// * `pval` - Post-divider ratio
+/// * `pval` - Post-divider ratio, `Error::InvalidDivider` if higher than 0x3
 ///
 /// # Example
 /// ```
@@ -17,13 +66,13 @@ extern crate lpc1347;
 ///
 /// fn init(p: init::Peripherals, r: init::Resources) {
 ///     // See admissible values in p. 44 of UM10524
-///     pll_init(&p, 0b11, 0b01);
+///     pll_init(&p, 0b11, 0b01).unwrap();
 /// }
 ///
 /// ```
-pub fn pll_init(syscon: &lpc1347::SYSCON, mval: u8, pval: u8) {
+pub fn pll_init(syscon: &lpc1347::SYSCON, mval: u8, pval: u8) -> Result<(), Error> {
     if pval > 0x3 {
-        panic!("the PLL does not admit p-values higher than 0x3");
+        return Err(Error::InvalidDivider);
     }
 
     // Make sure the system oscillator is powered on, otherwise no system signal
@@ -45,6 +94,9 @@ pub fn pll_init(syscon: &lpc1347::SYSCON, mval: u8, pval: u8) {
     while !syscon.syspllstat.read().lock().bit() {}
     syscon.mainclksel.modify(|_, w| w.sel().pll_output());
     syscon.clkoutsel.modify(|_, w| w.sel().main_clock());
+    set_main_clock((u32::from(mval) + 1) * CRYSTAL_HZ);
+
+    Ok(())
 }
 
 /// Set the System clock divider
@@ -77,6 +129,7 @@ pub fn sysclock_set_divider(syscon: &lpc1347::SYSCON, divider: u8) {
     unsafe {
         syscon.sysahbclkdiv.modify(|_, w| w.div().bits(divider));
     }
+    SYSTEM_DIVIDER.store(divider as usize, Ordering::Relaxed);
 }
 
 /// Initializes the watchdog
@@ -87,6 +140,8 @@ pub fn sysclock_set_divider(syscon: &lpc1347::SYSCON, divider: u8) {
 /// * `frequency` - Select the WWDT frequency
 /// * `divider` -  Divider value for Fclkana expressed as the actual divider, (2, 4, 6, 8, ... 62, 64) all even numbers up to 64.
 ///
+/// Returns `Error::InvalidDivider` if the divider is outside of 2-64.
+///
 /// Divider is calculated as follows:
 /// DIVSEL is the actual register value
 ///
@@ -107,7 +162,7 @@ pub fn sysclock_set_divider(syscon: &lpc1347::SYSCON, divider: u8) {
 ///     lpc1347::wwdt::clksel::CLKSELW::WATCHDOG_OSCILLATOR_,
 ///     lpc1347::syscon::wdtoscctrl::FREQSELW::_0_6_MHZ,
 ///     64
-/// );
+/// ).unwrap();
 /// ```
 ///
 pub fn wwdt_init(
@@ -118,11 +173,10 @@ pub fn wwdt_init(
     wwdt_clk_src: lpc1347::wwdt::clksel::CLKSELW,
     frequency: lpc1347::syscon::wdtoscctrl::FREQSELW,
     divider: u8,
-) {
-    if divider < 2 {
-        panic!("Too small divider! Minimum = 2");
-    } else if divider > 64 {
-        panic!("Too large divider! Maximum = 64");
+) -> Result<(), Error> {
+    match divider {
+        2..=64 => {}
+        _ => return Err(Error::InvalidDivider),
     }
 
     syscon.sysahbclkctrl.modify(|_, w| w.wwdt().enable());
@@ -147,6 +201,8 @@ pub fn wwdt_init(
         // Configure WWDT to act as wakeup interrupt
         syscon.starterp1.write(|w| w.wwdtint().enabled());
     }
+
+    Ok(())
 }
 
 /// Configure and setup the watchdog
@@ -159,6 +215,9 @@ pub fn wwdt_init(
 /// * `wd_warnint` - Specify the timer warning interrupt value, at which value should interrupt be
 /// triggered
 ///
+/// Returns `Error::InvalidTimeout` if the time-out does not fit in 24 bits, or
+/// `Error::InvalidWarning` if the warning value does not fit in 10 bits.
+///
 /// # Example
 /// ```
 /// clock::wwdt_configure(
@@ -168,7 +227,7 @@ pub fn wwdt_init(
 ///     true,
 ///     0xFFFF,
 ///     0xF
-/// );
+/// ).unwrap();
 /// ```
 pub fn wwdt_configure(
     wwdt: &lpc1347::WWDT,
@@ -177,7 +236,18 @@ pub fn wwdt_configure(
     wd_enable: bool,
     wd_timerconstant: u32,
     wd_warnint: u16,
-) {
+) -> Result<(), Error> {
+    // Configure the Timer Constant, the time-out value
+    // 24-bits max
+    if wd_timerconstant > 0xFF_FF_FF {
+        return Err(Error::InvalidTimeout);
+    }
+
+    // The warning is 10 bits
+    if wd_warnint > 0x3_FF {
+        return Err(Error::InvalidWarning);
+    }
+
     // Enable watchdog reset
     wwdt.mod_.modify(|_, w| w.wdreset().bit(wd_reset));
 
@@ -188,23 +258,17 @@ pub fn wwdt_configure(
     wwdt.mod_.modify(|_, w| w.wden().bit(wd_enable));
 
     unsafe {
-        // Configure the Timer Constant, the time-out value
-        // 24-bits max
-        if wd_timerconstant > 0xFF_FF_FF {
-            panic!("Invalid timer constant!");
-        }
         wwdt.tc.modify(|_, w| w.count().bits(wd_timerconstant));
 
         // Set the warning
-        if wd_warnint > 0x3_FF {
-            panic!("Invalid warnint constant!");
-        }
         wwdt.warnint.modify(|_, w| w.warnint().bits(wd_warnint));
 
         // Required for starting the watchdog, see 15.8.3 in UM10524
         wwdt.feed.write(|w| w.feed().bits(0xAA));
         wwdt.feed.write(|w| w.feed().bits(0x55));
     }
+
+    Ok(())
 }
 
 /// Feed the Watchdog
@@ -270,6 +334,11 @@ pub fn wwdt_as_mainclk(syscon: &lpc1347::SYSCON) {
         .mainclksel
         .modify(|_, w| w.sel().watchdog_oscillator());
 
+    // The oscillator runs at FREQSEL, divided by 2 * (1 + DIVSEL)
+    let wdtoscctrl = syscon.wdtoscctrl.read().bits();
+    let frequency = WDTOSC_KHZ[((wdtoscctrl >> 5) & 0xF) as usize] * 1000;
+    set_main_clock(frequency / (2 * (1 + (wdtoscctrl & 0x1F))));
+
     // Disable IRC clock on wakeup
     syscon
         .pdawakecfg
@@ -282,3 +351,58 @@ pub fn wwdt_as_mainclk(syscon: &lpc1347::SYSCON) {
     // Power down the IRC oscillator
     syscon.pdruncfg.modify(|_, w| w.irc_pd().powered_down());
 }
+
+/// Owned watchdog for use through the embedded-hal watchdog traits
+///
+/// The watchdog clock must be set up through `wwdt_init` first. Once started,
+/// the watchdog can not be disabled again.
+///
+/// # Example
+/// ```
+/// clock::wwdt_init(&mut p.core.NVIC, &p.device.SYSCON, &p.device.WWDT, false,
+///                  lpc1347::wwdt::clksel::CLKSELW::WATCHDOG_OSCILLATOR_,
+///                  lpc1347::syscon::wdtoscctrl::FREQSELW::_0_6_MHZ, 64);
+///
+/// let mut watchdog = clock::Watchdog::new(p.device.WWDT);
+/// watchdog.start(9375u32);
+/// ```
+#[cfg(feature = "hal")]
+pub struct Watchdog {
+    wwdt: lpc1347::WWDT,
+}
+
+#[cfg(feature = "hal")]
+impl Watchdog {
+    /// Take ownership of the watchdog
+    pub fn new(wwdt: lpc1347::WWDT) -> Self {
+        Watchdog { wwdt }
+    }
+
+    /// Release the watchdog
+    pub fn free(self) -> lpc1347::WWDT {
+        self.wwdt
+    }
+}
+
+#[cfg(feature = "hal")]
+impl ::embedded_hal::watchdog::Watchdog for Watchdog {
+    fn feed(&mut self) {
+        wwdt_feed(&self.wwdt);
+    }
+}
+
+#[cfg(feature = "hal")]
+impl ::embedded_hal::watchdog::WatchdogEnable for Watchdog {
+    /// Time-out in watchdog clock ticks, longer periods are limited to 24 bits
+    type Time = u32;
+
+    fn start<T>(&mut self, period: T)
+    where
+        T: Into<u32>,
+    {
+        let period = ::core::cmp::min(period.into(), 0xFF_FF_FF);
+
+        // Cannot fail, the period is in range and no warning is used
+        let _ = wwdt_configure(&self.wwdt, true, false, true, period, 0);
+    }
+}
@@ -0,0 +1,35 @@
+/// Errors reported by the drivers
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The A/D or pin interrupt channel does not exist
+    InvalidChannel,
+    /// The port has no pin at the given position
+    InvalidPin,
+    /// A clock divider is outside of the range supported by the hardware
+    InvalidDivider,
+    /// The watchdog time-out does not fit in 24 bits
+    InvalidTimeout,
+    /// The watchdog warning value does not fit in 10 bits
+    InvalidWarning,
+    /// The trigger is not supported, e.g. the sense and event of a pin
+    /// interrupt do not match, or a match register cannot start the ADC
+    InvalidTrigger,
+    /// A period or gate time is zero
+    InvalidPeriod,
+    /// The frequency cannot be generated from the peripheral clock
+    InvalidFrequency,
+    /// The duty cycle has a zero denominator
+    InvalidDuty,
+    /// A filter window or an oversampling factor is empty or too large
+    InvalidSamples,
+    /// The queue has no room for another entry
+    QueueFull,
+    /// The hardware did not finish in time
+    Timeout,
+    /// The terms of a pattern need more than the eight available bit slices
+    TooManySlices,
+    /// A product term of a pattern without any bit slices
+    EmptyTerm,
+    /// A pattern term was asked to end on a bit slice that is already used
+    SliceTaken,
+}
@@ -0,0 +1,303 @@
+#![allow(dead_code)]
+extern crate lpc1347;
+
+use adc::{self, Channel};
+use error::Error;
+
+/// Most extra bits of `Oversampler`, the result has to fit in 16 bits
+const MAX_EXTRA_BITS: u8 = 4;
+
+/// Longest window of `MovingAverage`, so that the sum fits in 32 bits
+const MAX_WINDOW: usize = 0x1_0000;
+
+/// Oversample and decimate for extra resolution
+///
+/// Sums 4^n samples and shifts the sum right by n, which gives n more bits
+/// than a single conversion. This only works if the signal has at least one
+/// LSB of noise on it.
+///
+/// # Example
+/// ```
+/// // 14 bits from the 12-bit ADC, reading AD5
+/// let mut oversampler = Oversampler::new(2).unwrap();
+/// let value = oversampler.read(&r.ADC, &pot).unwrap();
+///
+/// // Or fed from the burst buffer in the ADC task
+/// adc::read_burst(&r.ADC, &mut r.SCAN);
+/// if let Some(value) = r.SCAN.take(&pot).and_then(|v| r.OVERSAMPLER.push(v)) {
+///     // ...
+/// }
+/// ```
+#[derive(Copy, Clone)]
+pub struct Oversampler {
+    extra_bits: u8,
+    sum: u32,
+    count: u32,
+}
+
+impl Oversampler {
+    /// Create an oversampler
+    ///
+    /// # Arguments
+    /// * `extra_bits` - The bits to add (0-4), `Error::InvalidSamples` otherwise
+    pub fn new(extra_bits: u8) -> Result<Self, Error> {
+        if extra_bits > MAX_EXTRA_BITS {
+            return Err(Error::InvalidSamples);
+        }
+
+        Ok(Oversampler {
+            extra_bits,
+            sum: 0,
+            count: 0,
+        })
+    }
+
+    /// Get the number of bits added to each conversion
+    pub fn get_extra_bits(&self) -> u8 {
+        self.extra_bits
+    }
+
+    /// Get the number of samples in one result
+    pub fn get_samples(&self) -> u32 {
+        1 << (2 * self.extra_bits)
+    }
+
+    /// Add a sample, returns the result once enough samples are summed
+    pub fn push(&mut self, value: u16) -> Option<u16> {
+        self.sum += u32::from(value);
+        self.count += 1;
+        if self.count < self.get_samples() {
+            return None;
+        }
+
+        let value = self.sum >> self.extra_bits;
+        self.reset();
+        Some(value as u16)
+    }
+
+    /// Discard the samples summed so far
+    pub fn reset(&mut self) {
+        self.sum = 0;
+        self.count = 0;
+    }
+
+    /// Convert an analog pin until a result is ready, see `adc::read`
+    pub fn read<C: Channel>(&mut self, adc: &lpc1347::ADC, pin: &C) -> Result<u16, Error> {
+        self.reset();
+        loop {
+            if let Some(value) = self.push(adc::read(adc, pin)?) {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+/// Moving average over the latest samples
+///
+/// The window is borrowed storage, its length sets the number of samples.
+///
+/// # Example
+/// ```
+/// // Storage, e.g. a resource
+/// static mut WINDOW: [u16; 8] = [0; 8];
+///
+/// let mut average = MovingAverage::new(unsafe { &mut WINDOW }).unwrap();
+/// let value = average.push(adc::read(&r.ADC, &pot).unwrap());
+/// ```
+pub struct MovingAverage<'a> {
+    window: &'a mut [u16],
+    len: usize,
+    next: usize,
+    sum: u32,
+}
+
+impl<'a> MovingAverage<'a> {
+    /// Create an empty moving average
+    ///
+    /// Returns `Error::InvalidSamples` if the window is empty or longer than
+    /// 65536 samples.
+    pub fn new(window: &'a mut [u16]) -> Result<Self, Error> {
+        if window.is_empty() || window.len() > MAX_WINDOW {
+            return Err(Error::InvalidSamples);
+        }
+
+        Ok(MovingAverage {
+            window,
+            len: 0,
+            next: 0,
+            sum: 0,
+        })
+    }
+
+    /// Add a sample and get the average of the window
+    ///
+    /// Until the window is full, the average is taken over the samples so far.
+    pub fn push(&mut self, value: u16) -> u16 {
+        if self.is_full() {
+            self.sum -= u32::from(self.window[self.next]);
+        } else {
+            self.len += 1;
+        }
+
+        self.window[self.next] = value;
+        self.sum += u32::from(value);
+        self.next = (self.next + 1) % self.window.len();
+
+        self.average()
+    }
+
+    /// Get the average of the window, `None` if it is empty
+    pub fn get(&self) -> Option<u16> {
+        if self.len == 0 {
+            return None;
+        }
+        Some(self.average())
+    }
+
+    /// Determine if the window is full of samples
+    pub fn is_full(&self) -> bool {
+        self.len == self.window.len()
+    }
+
+    /// Discard all samples
+    pub fn reset(&mut self) {
+        self.len = 0;
+        self.next = 0;
+        self.sum = 0;
+    }
+
+    /// Rounded average of the samples in the window
+    fn average(&self) -> u16 {
+        let len = self.len as u32;
+        ((self.sum + len / 2) / len) as u16
+    }
+}
+
+/// Median over the latest samples, which removes spikes
+///
+/// The window is borrowed storage, its length sets the number of samples.
+/// Finding the median takes time in the square of the length, so keep the
+/// window short, and odd so that the median is a sample.
+///
+/// # Example
+/// ```
+/// // Storage, e.g. a resource
+/// static mut WINDOW: [u16; 5] = [0; 5];
+///
+/// let mut median = Median::new(unsafe { &mut WINDOW }).unwrap();
+/// let value = median.push(adc::read(&r.ADC, &pot).unwrap());
+/// ```
+pub struct Median<'a> {
+    window: &'a mut [u16],
+    len: usize,
+    next: usize,
+}
+
+impl<'a> Median<'a> {
+    /// Create an empty median filter
+    ///
+    /// Returns `Error::InvalidSamples` if the window is empty.
+    pub fn new(window: &'a mut [u16]) -> Result<Self, Error> {
+        if window.is_empty() {
+            return Err(Error::InvalidSamples);
+        }
+
+        Ok(Median {
+            window,
+            len: 0,
+            next: 0,
+        })
+    }
+
+    /// Add a sample and get the median of the window
+    ///
+    /// Until the window is full, the median is taken over the samples so far,
+    /// and for an even number of samples it is the lower one of the middle two.
+    pub fn push(&mut self, value: u16) -> u16 {
+        if !self.is_full() {
+            self.len += 1;
+        }
+
+        self.window[self.next] = value;
+        self.next = (self.next + 1) % self.window.len();
+
+        self.median()
+    }
+
+    /// Get the median of the window, `None` if it is empty
+    pub fn get(&self) -> Option<u16> {
+        if self.len == 0 {
+            return None;
+        }
+        Some(self.median())
+    }
+
+    /// Determine if the window is full of samples
+    pub fn is_full(&self) -> bool {
+        self.len == self.window.len()
+    }
+
+    /// Discard all samples
+    pub fn reset(&mut self) {
+        self.len = 0;
+        self.next = 0;
+    }
+
+    /// Find the sample with half of the others below it, without sorting
+    fn median(&self) -> u16 {
+        let samples = &self.window[..self.len];
+        let rank = (self.len - 1) / 2;
+
+        for &sample in samples {
+            let below = samples.iter().filter(|&&s| s < sample).count();
+            let equal = samples.iter().filter(|&&s| s == sample).count();
+            if below <= rank && rank < below + equal {
+                return sample;
+            }
+        }
+
+        // Every value of the window has a rank
+        samples[0]
+    }
+}
+
+/// Conversion of ADC values to millivolts
+///
+/// # Example
+/// ```
+/// // 3.3 V reference, 14 bits from the oversampler
+/// let scale = Scale::new(3300, 12 + oversampler.get_extra_bits());
+/// let mv = scale.millivolts(oversampler.read(&r.ADC, &pot).unwrap());
+/// ```
+#[derive(Copy, Clone)]
+pub struct Scale {
+    vref_mv: u16,
+    bits: u8,
+}
+
+impl Scale {
+    /// Configure the conversion
+    ///
+    /// # Arguments
+    /// * `vref_mv` - The voltage on VREFP in millivolts
+    /// * `bits` - The resolution of the values, 12 or 10 from the ADC, more
+    ///   after oversampling, at most 16
+    pub fn new(vref_mv: u16, bits: u8) -> Self {
+        Scale {
+            vref_mv,
+            bits: if bits > 16 { 16 } else { bits },
+        }
+    }
+
+    /// Get the reference voltage in millivolts
+    pub fn get_vref(&self) -> u16 {
+        self.vref_mv
+    }
+
+    /// Convert a value to millivolts, rounded
+    pub fn millivolts(&self, value: u16) -> u16 {
+        let full_scale = 1u32 << self.bits;
+        let mv = (u32::from(value) * u32::from(self.vref_mv) + full_scale / 2) >> self.bits;
+        mv as u16
+    }
+}
@@ -1,8 +1,13 @@
 #![allow(dead_code)]
 
 extern crate lpc1347;
-use lpc1347::Interrupt::{PIN_INT0, PIN_INT1, PIN_INT2, PIN_INT3, PIN_INT4, PIN_INT5, PIN_INT6,
-                         PIN_INT7};
+use core::marker::PhantomData;
+use core::ops::Deref;
+use error::Error;
+use iocon::{I2cMode, Mode};
+use lpc1347::Interrupt;
+use lpc1347::Interrupt::{GINT0, GINT1, PIN_INT0, PIN_INT1, PIN_INT2, PIN_INT3, PIN_INT4, PIN_INT5,
+                         PIN_INT6, PIN_INT7};
 
 /// Writes to a register using value and bitpos
 macro_rules! write_reg {
@@ -61,6 +66,53 @@ pub enum Sense {
     Level,
 }
 
+/// A grouped interrupt block and its interrupt
+pub trait GroupInterrupt: Deref<Target = lpc1347::gpio_group_int0::RegisterBlock> {
+    /// The interrupt raised by the group
+    const INTERRUPT: Interrupt;
+}
+
+impl GroupInterrupt for lpc1347::GPIO_GROUP_INT0 {
+    const INTERRUPT: Interrupt = GINT0;
+}
+
+impl GroupInterrupt for lpc1347::GPIO_GROUP_INT1 {
+    const INTERRUPT: Interrupt = GINT1;
+}
+
+/// Combine the pins of a grouped interrupt
+#[derive(Copy, Clone)]
+pub enum Combine {
+    /// Trigger when any pin matches its polarity
+    Or,
+    /// Trigger when all pins match their polarity
+    And,
+}
+
+/// Active level of a pin in a grouped interrupt
+#[derive(Copy, Clone)]
+pub enum Polarity {
+    /// The pin is active when low
+    Low,
+    /// The pin is active when high
+    High,
+}
+
+/// Condition that triggers a pin interrupt
+#[derive(Copy, Clone)]
+pub enum Trigger {
+    /// Rising edge
+    Rising,
+    /// Falling edge
+    Falling,
+    /// Both rising and falling edges
+    Both,
+    /// Level high
+    High,
+    /// Level low
+    Low,
+}
+
 /// Falling/rising edge detected
 #[derive(Copy, Clone)]
 pub enum Event {
@@ -97,11 +149,15 @@ pub fn init(syscon: &lpc1347::SYSCON, group0: bool, group1: bool) {
 /// Set pin for an interrupt
 ///
 /// # Arguments
-/// * `channel` - The target channel for the interrupt
+/// * `channel` - The target channel (0-7) for the interrupt
 /// * `port` - Which port (0/1) of pins to use
 /// * `bitpos` - Which pin to use
 /// * `sense` - Sense on edge or level when generating interrupts
 /// * `event` - Trigger on falling/rising or high/low
+///
+/// Returns `Error::InvalidChannel` for channels above 7, and
+/// `Error::InvalidTrigger` if an edge is sensed on high/low or a level on
+/// falling/rising.
 pub fn set_pin_interrupt(
     syscon: &lpc1347::SYSCON,
     nvic: &mut lpc1347::NVIC,
@@ -111,114 +167,185 @@ pub fn set_pin_interrupt(
     bitpos: u32,
     sense: Sense,
     event: Event,
-) {
-    // Calculate offset based on port
-    let offset: u32;
-    match port {
-        Port::Port0 => {
-            offset = 0u32;
-        }
-        Port::Port1 => {
-            offset = 24u32;
-        }
+) -> Result<(), Error> {
+    if channel > 7 {
+        return Err(Error::InvalidChannel);
     }
 
-    match channel {
-        0 => {
-            or_reg!(syscon.pintsel[0], bitpos + offset);
-            nvic.enable(PIN_INT0);
-        }
-        1 => {
-            or_reg!(syscon.pintsel[1], bitpos + offset);
-            nvic.enable(PIN_INT1);
-        }
-        2 => {
-            or_reg!(syscon.pintsel[2], bitpos + offset);
-            nvic.enable(PIN_INT2);
-        }
-        3 => {
-            or_reg!(syscon.pintsel[3], bitpos + offset);
-            nvic.enable(PIN_INT3);
-        }
-        4 => {
-            or_reg!(syscon.pintsel[4], bitpos + offset);
-            nvic.enable(PIN_INT4);
+    // Calculate offset based on port
+    let offset = match port {
+        Port::Port0 => 0u32,
+        Port::Port1 => 24u32,
+    };
+
+    // Either use edge detection or level detection
+    match (sense, event) {
+        (Sense::Edge, Event::Falling) => {
+            neg_and_reg!(&gpio_pin_int.isel, channel, 1);
+            or_reg!(&gpio_pin_int.ienf, channel, 1);
         }
-        5 => {
-            or_reg!(syscon.pintsel[5], bitpos + offset);
-            nvic.enable(PIN_INT5);
+        (Sense::Edge, Event::Rising) => {
+            neg_and_reg!(&gpio_pin_int.isel, channel, 1);
+            or_reg!(&gpio_pin_int.ienr, channel, 1);
         }
-        6 => {
-            or_reg!(syscon.pintsel[6], bitpos + offset);
-            nvic.enable(PIN_INT6);
+        (Sense::Level, Event::Low) => {
+            or_reg!(&gpio_pin_int.isel, channel, 1);
+            or_reg!(&gpio_pin_int.ienr, channel, 1);
+            neg_and_reg!(&gpio_pin_int.ienf, channel, 1);
         }
-        7 => {
-            or_reg!(syscon.pintsel[7], bitpos + offset);
-            nvic.enable(PIN_INT7);
+        (Sense::Level, Event::High) => {
+            or_reg!(&gpio_pin_int.isel, channel, 1);
+            or_reg!(&gpio_pin_int.ienr, channel, 1);
+            or_reg!(&gpio_pin_int.ienf, channel, 1);
         }
+        _ => return Err(Error::InvalidTrigger),
+    }
+
+    write_reg!(syscon.pintsel[channel as usize], 0, bitpos + offset);
+    nvic.enable(PIN_INTERRUPTS[channel as usize]);
 
-        _ => {
-            panic!("Invalid channel passed!");
+    Ok(())
+}
+
+/// Determine if a pin interrupt is pending
+///
+/// Returns `Error::InvalidChannel` for channels above 7.
+pub fn get_status(gpio_pin_int: &lpc1347::GPIO_PIN_INT, channel: u8) -> Result<bool, Error> {
+    if channel > 7 {
+        return Err(Error::InvalidChannel);
+    }
+
+    Ok(gpio_pin_int.ist.read().bits() & (1 << channel) != 0)
+}
+
+/// Clear the pin interrupt status
+///
+/// Returns `Error::InvalidChannel` for channels above 7.
+pub fn clear_status(gpio_pin_int: &lpc1347::GPIO_PIN_INT, channel: u8) -> Result<(), Error> {
+    if channel > 7 {
+        return Err(Error::InvalidChannel);
+    }
+
+    if gpio_pin_int.isel.read().bits() & (1 << channel) == 0 {
+        write_reg!(&gpio_pin_int.ist, channel, 1u32);
+    }
+
+    Ok(())
+}
+
+/// Configure a grouped interrupt
+///
+/// The pins of the group are selected with `add_group_pin`. A group interrupt
+/// fires when the selected pins match their polarity, combined by either AND
+/// or OR. Use `power::set_wakeup_interrupt` with `WakeupInts::GPIO0/GPIO1` to
+/// also wake up from deep-sleep or power-down on the group.
+///
+/// # Arguments
+/// * `gpio_group_int` - Which grouped interrupt (GPIO_GROUP_INT0/1) to configure
+/// * `combine` - Require all pins (AND) or any pin (OR) to match
+/// * `sense` - Trigger on the edge of the combined result or while it is active
+///
+/// # Example
+/// ```
+/// // Wake up when any of the keys on pio0_1 and pio1_19 is pressed (pulled low)
+/// gpio::init(&p.device.SYSCON, true, false);
+/// gpio::add_group_pin(&p.device.GPIO_GROUP_INT0, Port0, 1, Polarity::Low);
+/// gpio::add_group_pin(&p.device.GPIO_GROUP_INT0, Port1, 19, Polarity::Low);
+/// gpio::set_grouped_interrupt(
+///     &mut p.core.NVIC,
+///     &p.device.GPIO_GROUP_INT0,
+///     Combine::Or,
+///     Sense::Edge,
+/// );
+/// ```
+pub fn set_grouped_interrupt<G: GroupInterrupt>(
+    nvic: &mut lpc1347::NVIC,
+    gpio_group_int: &G,
+    combine: Combine,
+    sense: Sense,
+) {
+    match combine {
+        Combine::Or => {
+            neg_and_reg!(&gpio_group_int.ctrl, 1, 1);
+        }
+        Combine::And => {
+            or_reg!(&gpio_group_int.ctrl, 1, 1);
         }
     }
 
-    // Either use edge detection or level detection
     match sense {
         Sense::Edge => {
-            neg_and_reg!(&gpio_pin_int.isel, channel, 1);
+            neg_and_reg!(&gpio_group_int.ctrl, 2, 1);
+        }
+        Sense::Level => {
+            or_reg!(&gpio_group_int.ctrl, 2, 1);
+        }
+    }
 
-            match event {
-                Event::Falling => {
-                    or_reg!(&gpio_pin_int.ienf, channel, 1);
-                }
-                Event::Rising => {
-                    or_reg!(&gpio_pin_int.ienr, channel, 1);
-                }
+    // Changing the configuration may have raised the interrupt
+    clear_group_status(gpio_group_int);
 
-                _ => {
-                    panic!("invalid combination for sense and event, use Event::Falling or Event::Rising here")
-                }
-            }
-        }
+    nvic.enable(G::INTERRUPT);
+}
 
-        Sense::Level => {
-            or_reg!(&gpio_pin_int.isel, channel, 1);
-            or_reg!(&gpio_pin_int.ienr, channel, 1);
+/// Add a pin to a grouped interrupt
+///
+/// # Arguments
+/// * `port` - Which port (0/1) of pins to use
+/// * `bitpos` - Which pin to use
+/// * `polarity` - The level at which the pin contributes to the group
+pub fn add_group_pin(
+    gpio_group_int: &lpc1347::gpio_group_int0::RegisterBlock,
+    port: Port,
+    bitpos: u32,
+    polarity: Polarity,
+) {
+    let index = port as usize;
 
-            match event {
-                Event::Low => {
-                    neg_and_reg!(&gpio_pin_int.ienf, channel, 1);
-                }
-                Event::High => {
-                    or_reg!(&gpio_pin_int.ienf, channel, 1);
-                }
-                _ => panic!(
-                    "invalid combination for sense and event, use Event::Low or Event::High here"
-                ),
-            }
+    match polarity {
+        Polarity::Low => {
+            neg_and_reg!(gpio_group_int.port_pol[index], bitpos, 1);
+        }
+        Polarity::High => {
+            or_reg!(gpio_group_int.port_pol[index], bitpos, 1);
         }
     }
+    or_reg!(gpio_group_int.port_ena[index], bitpos, 1);
 }
 
-/// Determine if an interrupt is enabled or not
-// pub fn get_status(p: &Peripherals, channel: u8) -> bool {
-//     return (p.GPIO_PIN_INT.ist.read().bits() & (1 << channel)) == 1u32;
-// }
+/// Remove a pin from a grouped interrupt
+///
+/// # Arguments
+/// * `port` - Which port (0/1) of pins to use
+/// * `bitpos` - Which pin to use
+pub fn remove_group_pin(
+    gpio_group_int: &lpc1347::gpio_group_int0::RegisterBlock,
+    port: Port,
+    bitpos: u32,
+) {
+    neg_and_reg!(gpio_group_int.port_ena[port as usize], bitpos, 1);
+}
 
-/// Clear the pin interrupt status
-pub fn clear_status(gpio_pin_int: &lpc1347::GPIO_PIN_INT, channel: u8) {
-    if gpio_pin_int.isel.read().bits() & (1 << channel) == 0 {
-        write_reg!(&gpio_pin_int.ist, channel, 1u32);
-    }
+/// Determine if a grouped interrupt is pending
+pub fn get_group_status(gpio_group_int: &lpc1347::gpio_group_int0::RegisterBlock) -> bool {
+    gpio_group_int.ctrl.read().bits() & 1 != 0
 }
 
-/// Not implemented
-pub fn set_grouped_interrupt() {
-    panic!("not implemented");
+/// Clear the grouped interrupt status
+///
+/// In level mode the status can only be cleared once the group is no longer
+/// active.
+pub fn clear_group_status(gpio_group_int: &lpc1347::gpio_group_int0::RegisterBlock) {
+    // The status bit is cleared by writing a one, keep COMB and TRIG intact
+    or_reg!(&gpio_group_int.ctrl, 1);
 }
 
 /// Get current state of the pin
-pub fn get_pin_value(gpio_port: &lpc1347::GPIO_PORT, port: Port, bitpos: u32) -> bool {
+pub fn get_pin_value(
+    gpio_port: &lpc1347::gpio_port::RegisterBlock,
+    port: Port,
+    bitpos: u32,
+) -> bool {
     match port {
         Port::Port0 => {
             gpio_port.pin[0].read().bits() & (1 << bitpos) > 0
@@ -230,7 +357,12 @@ pub fn get_pin_value(gpio_port: &lpc1347::GPIO_PORT, port: Port, bitpos: u32) ->
 }
 
 /// Set value for pin
-pub fn set_pin_value(gpio_port: &lpc1347::GPIO_PORT, port: Port, bitpos: u32, value: bool) {
+pub fn set_pin_value(
+    gpio_port: &lpc1347::gpio_port::RegisterBlock,
+    port: Port,
+    bitpos: u32,
+    value: bool,
+) {
     match port {
         Port::Port0 => {
             unsafe {
@@ -247,7 +379,7 @@ pub fn set_pin_value(gpio_port: &lpc1347::GPIO_PORT, port: Port, bitpos: u32, va
 }
 
 /// Toggle value for pin
-pub fn toggle_pin_value(gpio_port: &lpc1347::GPIO_PORT, port: Port, bitpos: u32) {
+pub fn toggle_pin_value(gpio_port: &lpc1347::gpio_port::RegisterBlock, port: Port, bitpos: u32) {
     match port {
         Port::Port0 => {
             write_reg!(&gpio_port.not[0], bitpos, 1);
@@ -264,7 +396,12 @@ pub fn toggle_pin_value(gpio_port: &lpc1347::GPIO_PORT, port: Port, bitpos: u32)
 /// * `port` - Which port (0/1) of pins to use
 /// * `bitpos` - The pin number to use
 /// * `output`- Whether it should be an output pin (true) or an input pin (false)
-pub fn set_dir(gpio_port: &lpc1347::GPIO_PORT, port: Port, bitpos: u32, output: bool) {
+pub fn set_dir(
+    gpio_port: &lpc1347::gpio_port::RegisterBlock,
+    port: Port,
+    bitpos: u32,
+    output: bool,
+) {
     match port {
         Port::Port0 => {
             if output {
@@ -282,3 +419,1055 @@ pub fn set_dir(gpio_port: &lpc1347::GPIO_PORT, port: Port, bitpos: u32, output:
         }
     }
 }
+
+/// Shared access to the IOCON block for pins that have been split off
+fn iocon() -> &'static lpc1347::iocon::RegisterBlock {
+    unsafe { &*lpc1347::IOCON::ptr() }
+}
+
+/// Pin that has not been configured yet (type state)
+pub struct Unconfigured;
+
+/// Input mode (type state)
+pub struct Input<MODE> {
+    _mode: PhantomData<MODE>,
+}
+
+/// Floating input (type state)
+pub struct Floating;
+
+/// Pulled down input (type state)
+pub struct PullDown;
+
+/// Pulled up input (type state)
+pub struct PullUp;
+
+/// Output mode (type state)
+pub struct Output<MODE> {
+    _mode: PhantomData<MODE>,
+}
+
+/// Push-pull output (type state)
+pub struct PushPull;
+
+/// Open-drain output (type state)
+pub struct OpenDrain;
+
+/// Analog input mode (type state)
+pub struct Analog;
+
+/// Match output of a timer (type state), see `timer::MatchPin`
+pub struct MatchOutput;
+
+/// Capture input of a timer (type state), see `timer::CapturePin`
+pub struct Capture;
+
+/// The ports as types, so that pins can be checked to share a port
+pub mod ports {
+    /// Port 0 (type state)
+    pub struct Port0;
+
+    /// Port 1 (type state)
+    pub struct Port1;
+}
+
+/// A port as a type, see `ports`
+pub trait PortId {
+    /// The port
+    const PORT: Port;
+}
+
+impl PortId for ports::Port0 {
+    const PORT: Port = Port::Port0;
+}
+
+impl PortId for ports::Port1 {
+    const PORT: Port = Port::Port1;
+}
+
+/// Location of a typed pin
+pub trait Pin {
+    /// The port that the pin belongs to, as a type
+    type Port: PortId;
+    /// The port that the pin belongs to
+    const PORT: Port;
+    /// The position of the pin within its port
+    const BITPOS: u32;
+}
+
+/// Shared access to the GPIO block for pins that have been split off
+fn gpio_port() -> &'static lpc1347::gpio_port::RegisterBlock {
+    unsafe { &*lpc1347::GPIO_PORT::ptr() }
+}
+
+/// Defines the typed pins and the `Parts` returned by `split`
+macro_rules! pins {
+    ($($PIOX: ident: ($piox: ident, $port: ident, $bitpos: expr),)+) => {
+        /// The individual GPIO pins, obtained through `split`
+        pub struct Parts {
+            $(
+                /// Pin with the same name as the field
+                pub $piox: $PIOX<Unconfigured>,
+            )+
+            /// The mask register of port 0, see `PortBus`
+            pub mask0: PortMask<ports::Port0>,
+            /// The mask register of port 1, see `PortBus`
+            pub mask1: PortMask<ports::Port1>,
+        }
+
+        /// Split the GPIO block into independently owned pins
+        ///
+        /// Each pin configures its own IOCON register when changing mode, so
+        /// the IOCON block remains available to the other drivers. Make sure
+        /// `init` has been called first, to clock the GPIO and IOCON blocks.
+        ///
+        /// # Example
+        /// ```
+        /// gpio::init(&p.device.SYSCON, false, false);
+        /// let pins = gpio::split(p.device.GPIO_PORT);
+        ///
+        /// let mut led = pins.pio0_3.into_output();
+        /// led.set_value(true);
+        /// ```
+        pub fn split(_gpio_port: lpc1347::GPIO_PORT) -> Parts {
+            Parts {
+                $(
+                    $piox: $PIOX { _mode: PhantomData },
+                )+
+                mask0: PortMask { _port: PhantomData },
+                mask1: PortMask { _port: PhantomData },
+            }
+        }
+
+        $(
+            /// Typed GPIO pin, see `Parts`
+            pub struct $PIOX<MODE> {
+                _mode: PhantomData<MODE>,
+            }
+
+            impl<MODE> Pin for $PIOX<MODE> {
+                type Port = ports::$port;
+                const PORT: Port = Port::$port;
+                const BITPOS: u32 = $bitpos;
+            }
+
+            impl<MODE> $PIOX<Input<MODE>> {
+                /// Get current state of the pin
+                pub fn get_value(&self) -> bool {
+                    get_pin_value(gpio_port(), Port::$port, $bitpos)
+                }
+            }
+
+            impl<MODE> $PIOX<Output<MODE>> {
+                /// Get current state of the pin
+                pub fn get_value(&self) -> bool {
+                    get_pin_value(gpio_port(), Port::$port, $bitpos)
+                }
+
+                /// Set value for pin
+                pub fn set_value(&mut self, value: bool) {
+                    set_pin_value(gpio_port(), Port::$port, $bitpos, value);
+                }
+
+                /// Toggle value for pin
+                pub fn toggle(&mut self) {
+                    toggle_pin_value(gpio_port(), Port::$port, $bitpos);
+                }
+            }
+
+            #[cfg(feature = "hal")]
+            impl<MODE> ::embedded_hal::digital::v2::InputPin for $PIOX<Input<MODE>> {
+                type Error = ::void::Void;
+
+                fn is_high(&self) -> Result<bool, Self::Error> {
+                    Ok(self.get_value())
+                }
+
+                fn is_low(&self) -> Result<bool, Self::Error> {
+                    Ok(!self.get_value())
+                }
+            }
+
+            #[cfg(feature = "hal")]
+            impl<MODE> ::embedded_hal::digital::v2::OutputPin for $PIOX<Output<MODE>> {
+                type Error = ::void::Void;
+
+                fn set_low(&mut self) -> Result<(), Self::Error> {
+                    self.set_value(false);
+                    Ok(())
+                }
+
+                fn set_high(&mut self) -> Result<(), Self::Error> {
+                    self.set_value(true);
+                    Ok(())
+                }
+            }
+
+            #[cfg(feature = "hal")]
+            impl<MODE> ::embedded_hal::digital::v2::ToggleableOutputPin for $PIOX<Output<MODE>> {
+                type Error = ::void::Void;
+
+                fn toggle(&mut self) -> Result<(), Self::Error> {
+                    toggle_pin_value(gpio_port(), Port::$port, $bitpos);
+                    Ok(())
+                }
+            }
+        )+
+    };
+}
+
+/// Mode conversions for pins with a regular digital pad
+macro_rules! digital_pins {
+    ($($PIOX: ident,)+) => {
+        $(
+            impl<MODE> $PIOX<MODE> {
+                /// Configure the pin as a floating input
+                pub fn into_input(self) -> $PIOX<Input<Floating>> {
+                    self.set_digital(Mode::Inactive, false, false);
+                    $PIOX { _mode: PhantomData }
+                }
+
+                /// Configure the pin as an input with the pull-down resistor enabled
+                pub fn into_pull_down_input(self) -> $PIOX<Input<PullDown>> {
+                    self.set_digital(Mode::PullDown, false, false);
+                    $PIOX { _mode: PhantomData }
+                }
+
+                /// Configure the pin as an input with the pull-up resistor enabled
+                pub fn into_pull_up_input(self) -> $PIOX<Input<PullUp>> {
+                    self.set_digital(Mode::PullUp, false, false);
+                    $PIOX { _mode: PhantomData }
+                }
+
+                /// Configure the pin as a push-pull output
+                pub fn into_output(self) -> $PIOX<Output<PushPull>> {
+                    self.set_digital(Mode::Inactive, false, true);
+                    $PIOX { _mode: PhantomData }
+                }
+
+                /// Configure the pin as an open-drain output
+                pub fn into_open_drain_output(self) -> $PIOX<Output<OpenDrain>> {
+                    self.set_digital(Mode::Inactive, true, true);
+                    $PIOX { _mode: PhantomData }
+                }
+
+                /// Select the GPIO function with the given pad settings and direction
+                fn set_digital(&self, mode: Mode, open_drain: bool, output: bool) {
+                    ::iocon::$PIOX::config()
+                        .function(::iocon::Gpio)
+                        .mode(mode)
+                        .open_drain(open_drain)
+                        .apply(iocon());
+                    set_dir(gpio_port(), Self::PORT, Self::BITPOS, output);
+                }
+            }
+        )+
+    };
+}
+
+/// Mode conversions for the true open-drain I2C pads
+macro_rules! i2c_pins {
+    ($($PIOX: ident,)+) => {
+        $(
+            impl<MODE> $PIOX<MODE> {
+                /// Configure the pin as an input
+                pub fn into_input(self) -> $PIOX<Input<Floating>> {
+                    self.set_gpio(false);
+                    $PIOX { _mode: PhantomData }
+                }
+
+                /// Configure the pin as an open-drain output
+                ///
+                /// This pad has no push-pull driver, hence there is no `into_output`.
+                pub fn into_open_drain_output(self) -> $PIOX<Output<OpenDrain>> {
+                    self.set_gpio(true);
+                    $PIOX { _mode: PhantomData }
+                }
+
+                /// Select the GPIO function and direction
+                fn set_gpio(&self, output: bool) {
+                    ::iocon::$PIOX::config()
+                        .function(::iocon::Gpio)
+                        .i2c_mode(I2cMode::StandardIo)
+                        .apply(iocon());
+                    set_dir(gpio_port(), Self::PORT, Self::BITPOS, output);
+                }
+            }
+        )+
+    };
+}
+
+/// Analog conversion for the pins connected to the ADC
+macro_rules! analog_pins {
+    ($($PIOX: ident: $AD: ident,)+) => {
+        $(
+            impl<MODE> $PIOX<MODE> {
+                /// Configure the pin as an analog input
+                pub fn into_analog(self) -> $PIOX<Analog> {
+                    ::iocon::$PIOX::config()
+                        .function(::iocon::$AD)
+                        .mode(Mode::Inactive)
+                        .open_drain(false)
+                        .apply(iocon());
+                    set_dir(gpio_port(), Self::PORT, Self::BITPOS, false);
+                    $PIOX { _mode: PhantomData }
+                }
+            }
+        )+
+    };
+}
+
+pins! {
+    Pio0_0: (pio0_0, Port0, 0),
+    Pio0_1: (pio0_1, Port0, 1),
+    Pio0_2: (pio0_2, Port0, 2),
+    Pio0_3: (pio0_3, Port0, 3),
+    Pio0_4: (pio0_4, Port0, 4),
+    Pio0_5: (pio0_5, Port0, 5),
+    Pio0_6: (pio0_6, Port0, 6),
+    Pio0_7: (pio0_7, Port0, 7),
+    Pio0_8: (pio0_8, Port0, 8),
+    Pio0_9: (pio0_9, Port0, 9),
+    Pio0_10: (pio0_10, Port0, 10),
+    Pio0_11: (pio0_11, Port0, 11),
+    Pio0_12: (pio0_12, Port0, 12),
+    Pio0_13: (pio0_13, Port0, 13),
+    Pio0_14: (pio0_14, Port0, 14),
+    Pio0_15: (pio0_15, Port0, 15),
+    Pio0_16: (pio0_16, Port0, 16),
+    Pio0_17: (pio0_17, Port0, 17),
+    Pio0_18: (pio0_18, Port0, 18),
+    Pio0_19: (pio0_19, Port0, 19),
+    Pio0_20: (pio0_20, Port0, 20),
+    Pio0_21: (pio0_21, Port0, 21),
+    Pio0_22: (pio0_22, Port0, 22),
+    Pio0_23: (pio0_23, Port0, 23),
+    Pio1_0: (pio1_0, Port1, 0),
+    Pio1_1: (pio1_1, Port1, 1),
+    Pio1_2: (pio1_2, Port1, 2),
+    Pio1_3: (pio1_3, Port1, 3),
+    Pio1_4: (pio1_4, Port1, 4),
+    Pio1_5: (pio1_5, Port1, 5),
+    Pio1_6: (pio1_6, Port1, 6),
+    Pio1_7: (pio1_7, Port1, 7),
+    Pio1_8: (pio1_8, Port1, 8),
+    Pio1_9: (pio1_9, Port1, 9),
+    Pio1_10: (pio1_10, Port1, 10),
+    Pio1_11: (pio1_11, Port1, 11),
+    Pio1_12: (pio1_12, Port1, 12),
+    Pio1_13: (pio1_13, Port1, 13),
+    Pio1_14: (pio1_14, Port1, 14),
+    Pio1_15: (pio1_15, Port1, 15),
+    Pio1_16: (pio1_16, Port1, 16),
+    Pio1_17: (pio1_17, Port1, 17),
+    Pio1_18: (pio1_18, Port1, 18),
+    Pio1_19: (pio1_19, Port1, 19),
+    Pio1_20: (pio1_20, Port1, 20),
+    Pio1_21: (pio1_21, Port1, 21),
+    Pio1_22: (pio1_22, Port1, 22),
+    Pio1_23: (pio1_23, Port1, 23),
+    Pio1_24: (pio1_24, Port1, 24),
+    Pio1_25: (pio1_25, Port1, 25),
+    Pio1_26: (pio1_26, Port1, 26),
+    Pio1_27: (pio1_27, Port1, 27),
+    Pio1_28: (pio1_28, Port1, 28),
+    Pio1_29: (pio1_29, Port1, 29),
+    Pio1_30: (pio1_30, Port1, 30),
+    Pio1_31: (pio1_31, Port1, 31),
+}
+
+digital_pins! {
+    Pio0_0,
+    Pio0_1,
+    Pio0_2,
+    Pio0_3,
+    Pio0_6,
+    Pio0_7,
+    Pio0_8,
+    Pio0_9,
+    Pio0_10,
+    Pio0_11,
+    Pio0_12,
+    Pio0_13,
+    Pio0_14,
+    Pio0_15,
+    Pio0_16,
+    Pio0_17,
+    Pio0_18,
+    Pio0_19,
+    Pio0_20,
+    Pio0_21,
+    Pio0_22,
+    Pio0_23,
+    Pio1_0,
+    Pio1_1,
+    Pio1_2,
+    Pio1_3,
+    Pio1_4,
+    Pio1_5,
+    Pio1_6,
+    Pio1_7,
+    Pio1_8,
+    Pio1_9,
+    Pio1_10,
+    Pio1_11,
+    Pio1_12,
+    Pio1_13,
+    Pio1_14,
+    Pio1_15,
+    Pio1_16,
+    Pio1_17,
+    Pio1_18,
+    Pio1_19,
+    Pio1_20,
+    Pio1_21,
+    Pio1_22,
+    Pio1_23,
+    Pio1_24,
+    Pio1_25,
+    Pio1_26,
+    Pio1_27,
+    Pio1_28,
+    Pio1_29,
+    Pio1_30,
+    Pio1_31,
+}
+
+i2c_pins! {
+    Pio0_4,
+    Pio0_5,
+}
+
+analog_pins! {
+    Pio0_11: Ad0,
+    Pio0_12: Ad1,
+    Pio0_13: Ad2,
+    Pio0_14: Ad3,
+    Pio0_15: Ad4,
+    Pio0_16: Ad5,
+    Pio0_22: Ad6,
+    Pio0_23: Ad7,
+}
+
+/// Match output conversion for the pins connected to a timer match output
+macro_rules! match_pins {
+    ($($PIOX: ident: $FUNCTION: ident,)+) => {
+        $(
+            impl<MODE> $PIOX<MODE> {
+                /// Route the match output of its timer to the pin
+                pub fn into_match_output(self) -> $PIOX<MatchOutput> {
+                    ::iocon::$PIOX::config()
+                        .function(::iocon::$FUNCTION)
+                        .apply(iocon());
+                    $PIOX { _mode: PhantomData }
+                }
+            }
+        )+
+    };
+}
+
+// CT16B0_MAT3 and CT16B1_MAT2/MAT3 are not connected to any pin
+match_pins! {
+    Pio0_8: Ct16b0Mat0,
+    Pio1_13: Ct16b0Mat0,
+    Pio0_9: Ct16b0Mat1,
+    Pio1_14: Ct16b0Mat1,
+    Pio0_10: Ct16b0Mat2,
+    Pio1_15: Ct16b0Mat2,
+    Pio0_21: Ct16b1Mat0,
+    Pio0_22: Ct16b1Mat1,
+    Pio1_23: Ct16b1Mat1,
+    Pio0_18: Ct32b0Mat0,
+    Pio1_24: Ct32b0Mat0,
+    Pio0_19: Ct32b0Mat1,
+    Pio1_25: Ct32b0Mat1,
+    Pio0_1: Ct32b0Mat2,
+    Pio1_26: Ct32b0Mat2,
+    Pio0_11: Ct32b0Mat3,
+    Pio1_27: Ct32b0Mat3,
+    Pio0_13: Ct32b1Mat0,
+    Pio1_0: Ct32b1Mat0,
+    Pio0_14: Ct32b1Mat1,
+    Pio1_1: Ct32b1Mat1,
+    Pio0_15: Ct32b1Mat2,
+    Pio1_2: Ct32b1Mat2,
+    Pio0_16: Ct32b1Mat3,
+    Pio1_3: Ct32b1Mat3,
+}
+
+/// Capture conversion for the pins connected to a timer capture input
+macro_rules! capture_pins {
+    ($($PIOX: ident: $FUNCTION: ident,)+) => {
+        $(
+            impl<MODE> $PIOX<MODE> {
+                /// Route the pin to the capture input of its timer
+                pub fn into_capture(self) -> $PIOX<Capture> {
+                    ::iocon::$PIOX::config()
+                        .function(::iocon::$FUNCTION)
+                        .apply(iocon());
+                    $PIOX { _mode: PhantomData }
+                }
+            }
+        )+
+    };
+}
+
+capture_pins! {
+    Pio0_2: Ct16b0Cap0,
+    Pio1_16: Ct16b0Cap0,
+    Pio1_17: Ct16b0Cap1,
+    Pio0_20: Ct16b1Cap0,
+    Pio1_18: Ct16b1Cap1,
+    Pio0_17: Ct32b0Cap0,
+    Pio1_28: Ct32b0Cap0,
+    Pio1_29: Ct32b0Cap1,
+    Pio0_12: Ct32b1Cap0,
+    Pio1_4: Ct32b1Cap0,
+    Pio1_5: Ct32b1Cap1,
+}
+
+/// Ownership of the MASK register of a port, obtained through `split`
+pub struct PortMask<PORT> {
+    _port: PhantomData<PORT>,
+}
+
+/// A set of pins on one port that are read and written as a single value
+///
+/// The bus selects its pins through the MASK register of the port, so that
+/// `write` only needs a single store to the MPIN register. Pins that are not
+/// part of the bus are left untouched. Values are aligned to the lowest pin of
+/// the bus, e.g. a bus of pio1_13 to pio1_20 takes values in the range 0-255.
+/// Adding a pin of the other port does not compile.
+///
+/// # Example
+/// ```
+/// // 8-bit data bus of a parallel LCD on pio1_13 to pio1_20
+/// let pins = gpio::split(p.device.GPIO_PORT);
+/// let mut bus = gpio::PortBus::new(pins.mask1)
+///     .with(pins.pio1_13.into_output())
+///     .with(pins.pio1_14.into_output())
+///     .with(pins.pio1_15.into_output())
+///     .with(pins.pio1_16.into_output())
+///     .with(pins.pio1_17.into_output())
+///     .with(pins.pio1_18.into_output())
+///     .with(pins.pio1_19.into_output())
+///     .with(pins.pio1_20.into_output());
+///
+/// bus.write(0xA5);
+/// ```
+pub struct PortBus<PORT> {
+    pins: u32,
+    shift: u32,
+    _port: PhantomData<PORT>,
+}
+
+impl<PORT: PortId> PortBus<PORT> {
+    /// Create a bus without any pins
+    pub fn new(_mask: PortMask<PORT>) -> Self {
+        let bus = PortBus {
+            pins: 0,
+            shift: 0,
+            _port: PhantomData,
+        };
+        bus.set_mask();
+        bus
+    }
+
+    /// Add a pin to the bus
+    ///
+    /// The pin should already be configured as an input or output, and belong
+    /// to the same port as the bus.
+    pub fn with<P: Pin<Port = PORT>>(mut self, _pin: P) -> Self {
+        self.pins |= 1 << P::BITPOS;
+        self.shift = self.pins.trailing_zeros();
+        self.set_mask();
+        self
+    }
+
+    /// Release the mask register, the pins of the bus are not returned
+    pub fn free(self) -> PortMask<PORT> {
+        unsafe {
+            gpio_port().mask[PORT::PORT as usize].write(|w| w.bits(0));
+        }
+        PortMask { _port: PhantomData }
+    }
+
+    /// Read the pins of the bus
+    pub fn read(&self) -> u32 {
+        gpio_port().mpin[PORT::PORT as usize].read().bits() >> self.shift
+    }
+
+    /// Write all pins of the bus at once
+    pub fn write(&mut self, value: u32) {
+        unsafe {
+            gpio_port().mpin[PORT::PORT as usize].write(|w| w.bits(value << self.shift));
+        }
+    }
+
+    /// Set the pins of the bus that are 1 in `value`
+    pub fn set(&mut self, value: u32) {
+        unsafe {
+            gpio_port().set[PORT::PORT as usize]
+                .write(|w| w.bits((value << self.shift) & self.pins));
+        }
+    }
+
+    /// Clear the pins of the bus that are 1 in `value`
+    pub fn clear(&mut self, value: u32) {
+        unsafe {
+            gpio_port().clr[PORT::PORT as usize]
+                .write(|w| w.bits((value << self.shift) & self.pins));
+        }
+    }
+
+    /// Toggle the pins of the bus that are 1 in `value`
+    pub fn toggle(&mut self, value: u32) {
+        unsafe {
+            gpio_port().not[PORT::PORT as usize]
+                .write(|w| w.bits((value << self.shift) & self.pins));
+        }
+    }
+
+    /// Only let the pins of the bus through MPIN, a 0 in MASK enables a pin
+    fn set_mask(&self) {
+        unsafe {
+            gpio_port().mask[PORT::PORT as usize].write(|w| w.bits(!self.pins));
+        }
+    }
+}
+
+/// A pin interrupt channel (type state)
+pub trait PinIntChannel {
+    /// The channel number (0-7)
+    const CHANNEL: u32;
+    /// The NVIC line of the channel
+    const INTERRUPT: lpc1347::Interrupt;
+}
+
+/// Owned pin interrupt channel, obtained through `split_pin_interrupts`
+pub struct PinInterrupt<CH> {
+    _channel: PhantomData<CH>,
+}
+
+/// Shared access to the pin interrupt block for split channels
+fn gpio_pin_int() -> &'static lpc1347::gpio_pin_int::RegisterBlock {
+    unsafe { &*lpc1347::GPIO_PIN_INT::ptr() }
+}
+
+impl<CH: PinIntChannel> PinInterrupt<CH> {
+    /// Bind a pin to the channel and enable its interrupt
+    ///
+    /// Any previous binding of the channel is replaced.
+    ///
+    /// # Arguments
+    /// * `pin` - The pin to sense, typically configured as an input
+    /// * `trigger` - Trigger on an edge or while at a level
+    ///
+    /// # Example
+    /// ```
+    /// let pins = gpio::split(p.device.GPIO_PORT);
+    /// let ints = gpio::split_pin_interrupts(p.device.GPIO_PIN_INT);
+    ///
+    /// let button = pins.pio0_1.into_pull_up_input();
+    /// let mut pint0 = ints.pint0;
+    /// pint0.bind(&p.device.SYSCON, &mut p.core.NVIC, &button, Trigger::Falling);
+    /// ```
+    pub fn bind<P: Pin>(
+        &mut self,
+        syscon: &lpc1347::SYSCON,
+        nvic: &mut lpc1347::NVIC,
+        pin: &P,
+        trigger: Trigger,
+    ) {
+        let gpio_pin_int = gpio_pin_int();
+        let mask = 1 << CH::CHANNEL;
+
+        // Keep the channel quiet while it is reconfigured
+        self.disable(nvic);
+
+        self.select(syscon, pin);
+
+        unsafe {
+            match trigger {
+                Trigger::Rising | Trigger::Falling | Trigger::Both => {
+                    gpio_pin_int.isel.modify(|r, w| w.bits(r.bits() & !mask));
+
+                    match trigger {
+                        Trigger::Rising => {
+                            gpio_pin_int.sienr.write(|w| w.bits(mask));
+                        }
+                        Trigger::Falling => {
+                            gpio_pin_int.sienf.write(|w| w.bits(mask));
+                        }
+                        _ => {
+                            gpio_pin_int.sienr.write(|w| w.bits(mask));
+                            gpio_pin_int.sienf.write(|w| w.bits(mask));
+                        }
+                    }
+                }
+                Trigger::High | Trigger::Low => {
+                    gpio_pin_int.isel.modify(|r, w| w.bits(r.bits() | mask));
+
+                    // In level mode, IENF selects the active level
+                    if let Trigger::High = trigger {
+                        gpio_pin_int.sienf.write(|w| w.bits(mask));
+                    }
+                    gpio_pin_int.sienr.write(|w| w.bits(mask));
+                }
+            }
+        }
+
+        self.clear();
+        nvic.enable(CH::INTERRUPT);
+    }
+
+    /// Route a pin to the channel without enabling any interrupt
+    ///
+    /// This is enough for channels that are only used as pattern match inputs.
+    pub fn select<P: Pin>(&mut self, syscon: &lpc1347::SYSCON, _pin: &P) {
+        let offset = match P::PORT {
+            Port::Port0 => 0u32,
+            Port::Port1 => 24u32,
+        };
+        write_reg!(syscon.pintsel[CH::CHANNEL as usize], 0, P::BITPOS + offset);
+    }
+
+    /// Determine if the interrupt is pending
+    pub fn is_pending(&self) -> bool {
+        gpio_pin_int().ist.read().bits() & (1 << CH::CHANNEL) != 0
+    }
+
+    /// Determine if a rising edge was seen since the last `clear`
+    pub fn rising_detected(&self) -> bool {
+        gpio_pin_int().rise.read().bits() & (1 << CH::CHANNEL) != 0
+    }
+
+    /// Determine if a falling edge was seen since the last `clear`
+    pub fn falling_detected(&self) -> bool {
+        gpio_pin_int().fall.read().bits() & (1 << CH::CHANNEL) != 0
+    }
+
+    /// Clear the detected edges
+    ///
+    /// Level interrupts stay pending for as long as the level is present.
+    pub fn clear(&mut self) {
+        let gpio_pin_int = gpio_pin_int();
+        let mask = 1 << CH::CHANNEL;
+
+        unsafe {
+            gpio_pin_int.rise.write(|w| w.bits(mask));
+            gpio_pin_int.fall.write(|w| w.bits(mask));
+
+            // Writing IST in level mode would invert the active level
+            if gpio_pin_int.isel.read().bits() & mask == 0 {
+                gpio_pin_int.ist.write(|w| w.bits(mask));
+            }
+        }
+    }
+
+    /// Disable the interrupt and its NVIC line
+    pub fn disable(&mut self, nvic: &mut lpc1347::NVIC) {
+        let gpio_pin_int = gpio_pin_int();
+        let mask = 1 << CH::CHANNEL;
+
+        nvic.disable(CH::INTERRUPT);
+        unsafe {
+            gpio_pin_int.cienr.write(|w| w.bits(mask));
+            gpio_pin_int.cienf.write(|w| w.bits(mask));
+        }
+        self.clear();
+    }
+}
+
+/// Defines the pin interrupt channels and `split_pin_interrupts`
+macro_rules! pin_interrupts {
+    ($($PINTX: ident: ($pintx: ident, $channel: expr, $INTERRUPT: ident),)+) => {
+        /// The individual pin interrupt channels, obtained through `split_pin_interrupts`
+        pub struct PinInterrupts {
+            $(
+                /// Channel with the same number as the field
+                pub $pintx: PinInterrupt<$PINTX>,
+            )+
+        }
+
+        /// Split the pin interrupt block into independently owned channels
+        pub fn split_pin_interrupts(_gpio_pin_int: lpc1347::GPIO_PIN_INT) -> PinInterrupts {
+            PinInterrupts {
+                $(
+                    $pintx: PinInterrupt { _channel: PhantomData },
+                )+
+            }
+        }
+
+        $(
+            /// Pin interrupt channel, see `PinInterrupts`
+            pub struct $PINTX;
+
+            impl PinIntChannel for $PINTX {
+                const CHANNEL: u32 = $channel;
+                const INTERRUPT: lpc1347::Interrupt = $INTERRUPT;
+            }
+        )+
+    };
+}
+
+pin_interrupts! {
+    PinInt0: (pint0, 0, PIN_INT0),
+    PinInt1: (pint1, 1, PIN_INT1),
+    PinInt2: (pint2, 2, PIN_INT2),
+    PinInt3: (pint3, 3, PIN_INT3),
+    PinInt4: (pint4, 4, PIN_INT4),
+    PinInt5: (pint5, 5, PIN_INT5),
+    PinInt6: (pint6, 6, PIN_INT6),
+    PinInt7: (pint7, 7, PIN_INT7),
+}
+
+/// Pin interrupt lines, indexed by channel or bit slice
+const PIN_INTERRUPTS: [lpc1347::Interrupt; 8] = [
+    PIN_INT0, PIN_INT1, PIN_INT2, PIN_INT3, PIN_INT4, PIN_INT5, PIN_INT6, PIN_INT7,
+];
+
+/// Number of bit slices in the pattern match engine
+const PATTERN_SLICES: u32 = 8;
+
+/// Bit slice configuration that always contributes to a match
+const SLICE_CONSTANT_HIGH: u32 = 0x0;
+
+/// Bit slice configuration that never contributes to a match
+const SLICE_CONSTANT_LOW: u32 = 0x6;
+
+/// Condition evaluated by a bit slice of the pattern match engine
+#[derive(Copy, Clone)]
+pub enum Condition {
+    /// Rising edge since the pattern was last enabled
+    StickyRising = 0x1,
+    /// Falling edge since the pattern was last enabled
+    StickyFalling = 0x2,
+    /// Any edge since the pattern was last enabled
+    StickyEdge = 0x3,
+    /// Input is high
+    High = 0x4,
+    /// Input is low
+    Low = 0x5,
+    /// Edge in the current clock cycle
+    Event = 0x7,
+}
+
+/// Where the result of a pattern is reported
+#[derive(Copy, Clone)]
+pub enum Route {
+    /// Each product term raises the pin interrupt of its last bit slice
+    Interrupt,
+    /// The sum of all product terms drives the RXEV output to the core
+    Rxev,
+    /// Both of the above
+    Both,
+}
+
+/// Builder that compiles a sum-of-products expression for the pattern match engine
+///
+/// Every `slice` adds an input condition to the current product term, and
+/// `end_term` closes it. The inputs are the pins selected for the pin
+/// interrupt channels, see `PinInterrupt::select`.
+///
+/// # Example
+/// ```
+/// // Interlock: (pio0_1 high AND pio0_2 low) OR a rising edge on pio1_19
+/// pint0.select(&p.device.SYSCON, &door);
+/// pint1.select(&p.device.SYSCON, &guard);
+/// pint2.select(&p.device.SYSCON, &estop);
+///
+/// let pattern = PatternMatch::new()
+///     .slice(&pint0, Condition::High)
+///     .slice(&pint1, Condition::Low)
+///     .end_term()
+///     .slice(&pint2, Condition::StickyRising)
+///     .end_term_at(7)
+///     .compile()
+///     .unwrap();
+///
+/// // Raises PIN_INT1 for the first term and PIN_INT7 for the second
+/// gpio::set_pattern_match(&p.device.GPIO_PIN_INT, &mut p.core.NVIC, &pattern, Route::Interrupt);
+/// ```
+pub struct PatternMatch {
+    pmsrc: u32,
+    pmcfg: u32,
+    endpoints: u32,
+    next: u32,
+    term_start: u32,
+    error: Option<Error>,
+}
+
+impl Default for PatternMatch {
+    fn default() -> Self {
+        PatternMatch::new()
+    }
+}
+
+/// A compiled pattern, see `PatternMatch`
+pub struct Pattern {
+    pmsrc: u32,
+    pmcfg: u32,
+    endpoints: u32,
+}
+
+impl PatternMatch {
+    /// Start an empty expression
+    pub fn new() -> Self {
+        PatternMatch {
+            pmsrc: 0,
+            pmcfg: 0,
+            endpoints: 0,
+            next: 0,
+            term_start: 0,
+            error: None,
+        }
+    }
+
+    /// Add a condition on a channel input to the current product term
+    pub fn slice<CH: PinIntChannel>(self, _input: &PinInterrupt<CH>, condition: Condition) -> Self {
+        self.push(CH::CHANNEL, condition as u32)
+    }
+
+    /// Close the current product term on its last bit slice
+    pub fn end_term(mut self) -> Self {
+        if self.next == self.term_start {
+            self.fail(Error::EmptyTerm);
+        } else {
+            let endpoint = self.next - 1;
+            self.close(endpoint);
+        }
+        self
+    }
+
+    /// Close the current product term on the given bit slice
+    ///
+    /// The term is padded with constant bit slices, so that it raises the pin
+    /// interrupt with the same number as `slice`.
+    pub fn end_term_at(mut self, slice: u32) -> Self {
+        if self.next == self.term_start {
+            self.fail(Error::EmptyTerm);
+            return self;
+        }
+        if slice >= PATTERN_SLICES {
+            self.fail(Error::TooManySlices);
+            return self;
+        }
+        if slice < self.next - 1 {
+            self.fail(Error::SliceTaken);
+            return self;
+        }
+
+        while self.next <= slice {
+            self = self.push(0, SLICE_CONSTANT_HIGH);
+        }
+        self.close(slice);
+        self
+    }
+
+    /// Compile the expression into its register values
+    ///
+    /// A product term that is still open is closed first.
+    pub fn compile(mut self) -> Result<Pattern, Error> {
+        if self.next != self.term_start {
+            self = self.end_term();
+        }
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        // Unused slices can never match, or they would form a term ending on slice 7
+        while self.next < PATTERN_SLICES {
+            self.pmcfg |= SLICE_CONSTANT_LOW << (8 + 3 * self.next);
+            self.next += 1;
+        }
+
+        Ok(Pattern {
+            pmsrc: self.pmsrc,
+            pmcfg: self.pmcfg,
+            endpoints: self.endpoints,
+        })
+    }
+
+    /// Append a bit slice to the current product term
+    fn push(mut self, input: u32, config: u32) -> Self {
+        if self.next >= PATTERN_SLICES {
+            self.fail(Error::TooManySlices);
+        } else {
+            self.pmsrc |= input << (8 + 3 * self.next);
+            self.pmcfg |= config << (8 + 3 * self.next);
+            self.next += 1;
+        }
+        self
+    }
+
+    /// Mark a bit slice as the endpoint of the current product term
+    fn close(&mut self, endpoint: u32) {
+        // Slice 7 is always an endpoint and has no PROD_ENDPTS bit
+        if endpoint < PATTERN_SLICES - 1 {
+            self.pmcfg |= 1 << endpoint;
+        }
+        self.endpoints |= 1 << endpoint;
+        self.term_start = self.next;
+    }
+
+    /// Remember the first problem found
+    fn fail(&mut self, error: Error) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+}
+
+/// Switch the pin interrupt block to pattern match mode
+///
+/// The pin interrupts of the channels stop working as regular pin interrupts
+/// until `clear_pattern_match` is called. Sticky edge conditions are reset by
+/// enabling the pattern again.
+///
+/// # Arguments
+/// * `pattern` - A pattern compiled with `PatternMatch`
+/// * `route` - Report matches through the pin interrupts, RXEV or both
+pub fn set_pattern_match(
+    gpio_pin_int: &lpc1347::GPIO_PIN_INT,
+    nvic: &mut lpc1347::NVIC,
+    pattern: &Pattern,
+    route: Route,
+) {
+    let (interrupt, rxev) = match route {
+        Route::Interrupt => (true, false),
+        Route::Rxev => (false, true),
+        Route::Both => (true, true),
+    };
+
+    unsafe {
+        // Sources first, then the configuration, as recommended by UM10524
+        gpio_pin_int.pmsrc.write(|w| w.bits(pattern.pmsrc));
+        gpio_pin_int.pmcfg.write(|w| w.bits(pattern.pmcfg));
+        gpio_pin_int
+            .pmctrl
+            .write(|w| w.bits(1 | if rxev { 1 << 1 } else { 0 }));
+    }
+
+    for slice in 0..PATTERN_SLICES {
+        if pattern.endpoints & (1 << slice) != 0 {
+            if interrupt {
+                nvic.enable(PIN_INTERRUPTS[slice as usize]);
+            } else {
+                nvic.disable(PIN_INTERRUPTS[slice as usize]);
+            }
+        }
+    }
+}
+
+/// Return the pin interrupt block to regular pin interrupts
+pub fn clear_pattern_match(gpio_pin_int: &lpc1347::GPIO_PIN_INT) {
+    unsafe {
+        gpio_pin_int.pmctrl.write(|w| w.bits(0));
+    }
+}
+
+/// Get the product terms that currently match, one bit per endpoint slice
+pub fn get_pattern_status(gpio_pin_int: &lpc1347::GPIO_PIN_INT) -> u8 {
+    (gpio_pin_int.pmctrl.read().bits() >> 24) as u8
+}
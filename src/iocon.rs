@@ -0,0 +1,476 @@
+#![allow(dead_code)]
+extern crate lpc1347;
+
+use core::marker::PhantomData;
+use error::Error;
+use gpio::Port;
+
+/// Function select field
+const FUNC: u32 = 0x7;
+
+/// Pull-up/pull-down mode field
+const MODE: u32 = 0x3 << 3;
+
+/// Hysteresis bit
+const HYS: u32 = 1 << 5;
+
+/// Input inversion bit
+const INV: u32 = 1 << 6;
+
+/// Analog/digital mode bit of the analog pins, set for digital operation
+const ADMODE: u32 = 1 << 7;
+
+/// I2C mode field of the I2C pins
+const I2CMODE: u32 = 0x3 << 8;
+
+/// Open-drain bit
+const OD: u32 = 1 << 10;
+
+/// Resistor mode of a digital pin
+#[derive(Copy, Clone)]
+pub enum Mode {
+    /// No pull-up or pull-down resistor
+    Inactive = 0x0,
+    /// Pull-down resistor enabled
+    PullDown = 0x1,
+    /// Pull-up resistor enabled
+    PullUp = 0x2,
+    /// Repeater mode, keeps the last driven level
+    Repeater = 0x3,
+}
+
+/// Mode of the true open-drain I2C pins
+#[derive(Copy, Clone)]
+pub enum I2cMode {
+    /// Standard-mode and fast-mode I2C
+    StandardFast = 0x0,
+    /// Standard I/O functionality
+    StandardIo = 0x1,
+    /// Fast-mode Plus I2C
+    FastPlus = 0x2,
+}
+
+/// A pin with an IOCON register
+pub trait IoPin {
+    /// Whether bit 7 of the register selects analog or digital mode
+    const ADMODE: bool;
+
+    /// Read-modify-write the IOCON register of the pin
+    fn modify(iocon: &lpc1347::iocon::RegisterBlock, mask: u32, value: u32);
+}
+
+/// A pin with a regular digital pad, supporting pull resistors and open-drain
+pub trait DigitalPad: IoPin {}
+
+/// A pin with a true open-drain I2C pad
+pub trait I2cPad: IoPin {}
+
+/// A signal that can be routed to a pin
+pub trait Signal {
+    /// Whether the signal is an analog input
+    const ANALOG: bool;
+}
+
+/// A signal that is available on the pin `PIN`
+pub trait Function<PIN>: Signal {
+    /// The FUNC value selecting the signal on `PIN`
+    const FUNC: u32;
+}
+
+/// IOCON settings for a pin, see `IoPin`
+///
+/// Only the fields that are set through the builder are changed by `apply`,
+/// the rest of the register is left as it is. Selecting a function that is not
+/// available on the pin does not compile.
+///
+/// # Example
+/// ```
+/// // CT32B0_MAT0 on its alternate location, with the pull-up disabled
+/// iocon::Pio1_24::config()
+///     .function(iocon::Ct32b0Mat0)
+///     .mode(iocon::Mode::Inactive)
+///     .apply(&p.device.IOCON);
+/// ```
+pub struct Config<PIN> {
+    mask: u32,
+    value: u32,
+    _pin: PhantomData<PIN>,
+}
+
+impl<PIN: IoPin> Config<PIN> {
+    /// Start a configuration that leaves the register unchanged
+    pub fn new() -> Self {
+        Config {
+            mask: 0,
+            value: 0,
+            _pin: PhantomData,
+        }
+    }
+
+    /// Select the function of the pin
+    pub fn function<F: Function<PIN>>(self, _function: F) -> Self {
+        let config = self.set(FUNC, F::FUNC);
+
+        // Analog pins must be switched between analog and digital mode as well
+        if PIN::ADMODE {
+            config.set(ADMODE, if F::ANALOG { 0 } else { ADMODE })
+        } else {
+            config
+        }
+    }
+
+    /// Write the configuration to the IOCON register of the pin
+    pub fn apply(&self, iocon: &lpc1347::iocon::RegisterBlock) {
+        PIN::modify(iocon, self.mask, self.value);
+    }
+
+    /// Replace a field
+    fn set(mut self, field: u32, value: u32) -> Self {
+        self.mask |= field;
+        self.value = (self.value & !field) | (value & field);
+        self
+    }
+}
+
+impl<PIN: IoPin> Default for Config<PIN> {
+    fn default() -> Self {
+        Config::new()
+    }
+}
+
+impl<PIN: DigitalPad> Config<PIN> {
+    /// Select the pull-up/pull-down resistor mode
+    pub fn mode(self, mode: Mode) -> Self {
+        self.set(MODE, (mode as u32) << 3)
+    }
+
+    /// Enable or disable input hysteresis
+    pub fn hysteresis(self, enabled: bool) -> Self {
+        self.set(HYS, if enabled { HYS } else { 0 })
+    }
+
+    /// Invert the input
+    pub fn invert(self, enabled: bool) -> Self {
+        self.set(INV, if enabled { INV } else { 0 })
+    }
+
+    /// Use open-drain instead of push-pull output
+    pub fn open_drain(self, enabled: bool) -> Self {
+        self.set(OD, if enabled { OD } else { 0 })
+    }
+}
+
+impl<PIN: I2cPad> Config<PIN> {
+    /// Select the I2C mode of the pad
+    ///
+    /// Use `I2cMode::StandardIo` when the pin is used as GPIO.
+    pub fn i2c_mode(self, mode: I2cMode) -> Self {
+        self.set(I2CMODE, (mode as u32) << 8)
+    }
+}
+
+/// Configuration of an unused pin with the lowest leakage, see `park`
+macro_rules! parked {
+    (DigitalPad, $PIOX: ident) => {
+        $PIOX::config()
+            .function(Gpio)
+            .mode(Mode::Inactive)
+            .hysteresis(false)
+            .invert(false)
+            .open_drain(false)
+    };
+    (I2cPad, $PIOX: ident) => {
+        $PIOX::config().function(Gpio).i2c_mode(I2cMode::StandardIo)
+    };
+}
+
+/// Defines the pins, their IOCON registers and access by port and position
+macro_rules! pins {
+    ($($PIOX: ident: ($register: ident, $port: ident, $bitpos: expr, $Pad: ident, $admode: expr),)+) => {
+        $(
+            /// Pin with an IOCON register, see `Config`
+            pub struct $PIOX;
+
+            impl $PIOX {
+                /// Start a configuration for the pin
+                pub fn config() -> Config<$PIOX> {
+                    Config::new()
+                }
+            }
+
+            impl IoPin for $PIOX {
+                const ADMODE: bool = $admode;
+
+                fn modify(iocon: &lpc1347::iocon::RegisterBlock, mask: u32, value: u32) {
+                    unsafe {
+                        iocon
+                            .$register
+                            .modify(|r, w| w.bits((r.bits() & !mask) | value));
+                    }
+                }
+            }
+
+            impl $Pad for $PIOX {}
+        )+
+
+        /// Read the IOCON register of a pin
+        ///
+        /// Returns `Error::InvalidPin` if the port has no pin at `bitpos`.
+        pub fn read(iocon: &lpc1347::iocon::RegisterBlock, port: Port, bitpos: u32) -> Result<u32, Error> {
+            match (port, bitpos) {
+                $(
+                    (Port::$port, $bitpos) => Ok(iocon.$register.read().bits()),
+                )+
+                _ => Err(Error::InvalidPin),
+            }
+        }
+
+        /// Write the IOCON register of a pin, e.g. with a value saved by `read`
+        ///
+        /// Returns `Error::InvalidPin` if the port has no pin at `bitpos`.
+        pub fn write(
+            iocon: &lpc1347::iocon::RegisterBlock,
+            port: Port,
+            bitpos: u32,
+            value: u32,
+        ) -> Result<(), Error> {
+            match (port, bitpos) {
+                $(
+                    (Port::$port, $bitpos) => unsafe {
+                        iocon.$register.write(|w| w.bits(value));
+                    },
+                )+
+                _ => return Err(Error::InvalidPin),
+            }
+
+            Ok(())
+        }
+
+        /// Configure an unused pin for the lowest leakage
+        ///
+        /// Selects the GPIO function in digital mode and disables the pull
+        /// resistors, hysteresis, inversion and open-drain mode. The GPIO
+        /// direction and value are not changed.
+        ///
+        /// Returns `Error::InvalidPin` if the port has no pin at `bitpos`.
+        pub fn park(iocon: &lpc1347::iocon::RegisterBlock, port: Port, bitpos: u32) -> Result<(), Error> {
+            match (port, bitpos) {
+                $(
+                    (Port::$port, $bitpos) => parked!($Pad, $PIOX).apply(iocon),
+                )+
+                _ => return Err(Error::InvalidPin),
+            }
+
+            Ok(())
+        }
+    };
+}
+
+/// Defines the signals that can be routed to pins
+macro_rules! signals {
+    ($($SIGNAL: ident: $analog: expr,)+) => {
+        $(
+            /// Pin function, see `Function`
+            pub struct $SIGNAL;
+
+            impl Signal for $SIGNAL {
+                const ANALOG: bool = $analog;
+            }
+        )+
+    };
+}
+
+/// Defines which signals are available on a pin, and the FUNC value selecting them
+macro_rules! functions {
+    ($($PIOX: ident: [$($SIGNAL: ident: $func: expr),+],)+) => {
+        $(
+            $(
+                impl Function<$PIOX> for $SIGNAL {
+                    const FUNC: u32 = $func;
+                }
+            )+
+        )+
+    };
+}
+
+pins! {
+    Pio0_0: (reset_pio0_0, Port0, 0, DigitalPad, false),
+    Pio0_1: (pio0_1, Port0, 1, DigitalPad, false),
+    Pio0_2: (pio0_2, Port0, 2, DigitalPad, false),
+    Pio0_3: (pio0_3, Port0, 3, DigitalPad, false),
+    Pio0_4: (pio0_4, Port0, 4, I2cPad, false),
+    Pio0_5: (pio0_5, Port0, 5, I2cPad, false),
+    Pio0_6: (pio0_6, Port0, 6, DigitalPad, false),
+    Pio0_7: (pio0_7, Port0, 7, DigitalPad, false),
+    Pio0_8: (pio0_8, Port0, 8, DigitalPad, false),
+    Pio0_9: (pio0_9, Port0, 9, DigitalPad, false),
+    Pio0_10: (swclk_pio0_10, Port0, 10, DigitalPad, false),
+    Pio0_11: (tdi_pio0_11, Port0, 11, DigitalPad, true),
+    Pio0_12: (tms_pio0_12, Port0, 12, DigitalPad, true),
+    Pio0_13: (tdo_pio0_13, Port0, 13, DigitalPad, true),
+    Pio0_14: (trst_pio0_14, Port0, 14, DigitalPad, true),
+    Pio0_15: (swdio_pio0_15, Port0, 15, DigitalPad, true),
+    Pio0_16: (pio0_16, Port0, 16, DigitalPad, true),
+    Pio0_17: (pio0_17, Port0, 17, DigitalPad, false),
+    Pio0_18: (pio0_18, Port0, 18, DigitalPad, false),
+    Pio0_19: (pio0_19, Port0, 19, DigitalPad, false),
+    Pio0_20: (pio0_20, Port0, 20, DigitalPad, false),
+    Pio0_21: (pio0_21, Port0, 21, DigitalPad, false),
+    Pio0_22: (pio0_22, Port0, 22, DigitalPad, true),
+    Pio0_23: (pio0_23, Port0, 23, DigitalPad, true),
+    Pio1_0: (pio1_0, Port1, 0, DigitalPad, false),
+    Pio1_1: (pio1_1, Port1, 1, DigitalPad, false),
+    Pio1_2: (pio1_2, Port1, 2, DigitalPad, false),
+    Pio1_3: (pio1_3, Port1, 3, DigitalPad, false),
+    Pio1_4: (pio1_4, Port1, 4, DigitalPad, false),
+    Pio1_5: (pio1_5, Port1, 5, DigitalPad, false),
+    Pio1_6: (pio1_6, Port1, 6, DigitalPad, false),
+    Pio1_7: (pio1_7, Port1, 7, DigitalPad, false),
+    Pio1_8: (pio1_8, Port1, 8, DigitalPad, false),
+    Pio1_9: (pio1_9, Port1, 9, DigitalPad, false),
+    Pio1_10: (pio1_10, Port1, 10, DigitalPad, false),
+    Pio1_11: (pio1_11, Port1, 11, DigitalPad, false),
+    Pio1_12: (pio1_12, Port1, 12, DigitalPad, false),
+    Pio1_13: (pio1_13, Port1, 13, DigitalPad, false),
+    Pio1_14: (pio1_14, Port1, 14, DigitalPad, false),
+    Pio1_15: (pio1_15, Port1, 15, DigitalPad, false),
+    Pio1_16: (pio1_16, Port1, 16, DigitalPad, false),
+    Pio1_17: (pio1_17, Port1, 17, DigitalPad, false),
+    Pio1_18: (pio1_18, Port1, 18, DigitalPad, false),
+    Pio1_19: (pio1_19, Port1, 19, DigitalPad, false),
+    Pio1_20: (pio1_20, Port1, 20, DigitalPad, false),
+    Pio1_21: (pio1_21, Port1, 21, DigitalPad, false),
+    Pio1_22: (pio1_22, Port1, 22, DigitalPad, false),
+    Pio1_23: (pio1_23, Port1, 23, DigitalPad, false),
+    Pio1_24: (pio1_24, Port1, 24, DigitalPad, false),
+    Pio1_25: (pio1_25, Port1, 25, DigitalPad, false),
+    Pio1_26: (pio1_26, Port1, 26, DigitalPad, false),
+    Pio1_27: (pio1_27, Port1, 27, DigitalPad, false),
+    Pio1_28: (pio1_28, Port1, 28, DigitalPad, false),
+    Pio1_29: (pio1_29, Port1, 29, DigitalPad, false),
+    Pio1_30: (pio1_30, Port1, 30, DigitalPad, false),
+    Pio1_31: (pio1_31, Port1, 31, DigitalPad, false),
+}
+
+signals! {
+    Gpio: false,
+    Reset: false,
+    Swclk: false,
+    Swdio: false,
+    Tdi: false,
+    Tms: false,
+    Tdo: false,
+    Trst: false,
+    Clkout: false,
+    UsbFtoggle: false,
+    UsbVbus: false,
+    UsbConnect: false,
+    Scl: false,
+    Sda: false,
+    Rxd: false,
+    Txd: false,
+    Cts: false,
+    Rts: false,
+    Dtr: false,
+    Dsr: false,
+    Dcd: false,
+    Ri: false,
+    Sclk: false,
+    Sck0: false,
+    Ssel0: false,
+    Miso0: false,
+    Mosi0: false,
+    Sck1: false,
+    Ssel1: false,
+    Miso1: false,
+    Mosi1: false,
+    Ct16b0Mat0: false,
+    Ct16b0Mat1: false,
+    Ct16b0Mat2: false,
+    Ct16b0Cap0: false,
+    Ct16b0Cap1: false,
+    Ct16b1Mat0: false,
+    Ct16b1Mat1: false,
+    Ct16b1Cap0: false,
+    Ct16b1Cap1: false,
+    Ct32b0Mat0: false,
+    Ct32b0Mat1: false,
+    Ct32b0Mat2: false,
+    Ct32b0Mat3: false,
+    Ct32b0Cap0: false,
+    Ct32b0Cap1: false,
+    Ct32b1Mat0: false,
+    Ct32b1Mat1: false,
+    Ct32b1Mat2: false,
+    Ct32b1Mat3: false,
+    Ct32b1Cap0: false,
+    Ct32b1Cap1: false,
+    Ad0: true,
+    Ad1: true,
+    Ad2: true,
+    Ad3: true,
+    Ad4: true,
+    Ad5: true,
+    Ad6: true,
+    Ad7: true,
+}
+
+// See the IOCON register descriptions in chapter 7 of UM10524
+functions! {
+    Pio0_0: [Reset: 0x0, Gpio: 0x1],
+    Pio0_1: [Gpio: 0x0, Clkout: 0x1, Ct32b0Mat2: 0x2, UsbFtoggle: 0x3],
+    Pio0_2: [Gpio: 0x0, Ssel0: 0x1, Ct16b0Cap0: 0x2],
+    Pio0_3: [Gpio: 0x0, UsbVbus: 0x1],
+    Pio0_4: [Gpio: 0x0, Scl: 0x1],
+    Pio0_5: [Gpio: 0x0, Sda: 0x1],
+    Pio0_6: [Gpio: 0x0, UsbConnect: 0x1, Sck0: 0x2],
+    Pio0_7: [Gpio: 0x0, Cts: 0x1],
+    Pio0_8: [Gpio: 0x0, Miso0: 0x1, Ct16b0Mat0: 0x2],
+    Pio0_9: [Gpio: 0x0, Mosi0: 0x1, Ct16b0Mat1: 0x2],
+    Pio0_10: [Swclk: 0x0, Gpio: 0x1, Sck0: 0x2, Ct16b0Mat2: 0x3],
+    Pio0_11: [Tdi: 0x0, Gpio: 0x1, Ad0: 0x2, Ct32b0Mat3: 0x3],
+    Pio0_12: [Tms: 0x0, Gpio: 0x1, Ad1: 0x2, Ct32b1Cap0: 0x3],
+    Pio0_13: [Tdo: 0x0, Gpio: 0x1, Ad2: 0x2, Ct32b1Mat0: 0x3],
+    Pio0_14: [Trst: 0x0, Gpio: 0x1, Ad3: 0x2, Ct32b1Mat1: 0x3],
+    Pio0_15: [Swdio: 0x0, Gpio: 0x1, Ad4: 0x2, Ct32b1Mat2: 0x3],
+    Pio0_16: [Gpio: 0x0, Ad5: 0x1, Ct32b1Mat3: 0x2],
+    Pio0_17: [Gpio: 0x0, Rts: 0x1, Ct32b0Cap0: 0x2, Sclk: 0x3],
+    Pio0_18: [Gpio: 0x0, Rxd: 0x1, Ct32b0Mat0: 0x2],
+    Pio0_19: [Gpio: 0x0, Txd: 0x1, Ct32b0Mat1: 0x2],
+    Pio0_20: [Gpio: 0x0, Ct16b1Cap0: 0x1],
+    Pio0_21: [Gpio: 0x0, Ct16b1Mat0: 0x1, Mosi1: 0x2],
+    Pio0_22: [Gpio: 0x0, Ad6: 0x1, Ct16b1Mat1: 0x2, Miso1: 0x3],
+    Pio0_23: [Gpio: 0x0, Ad7: 0x1],
+    Pio1_0: [Gpio: 0x0, Ct32b1Mat0: 0x1],
+    Pio1_1: [Gpio: 0x0, Ct32b1Mat1: 0x1],
+    Pio1_2: [Gpio: 0x0, Ct32b1Mat2: 0x1],
+    Pio1_3: [Gpio: 0x0, Ct32b1Mat3: 0x1],
+    Pio1_4: [Gpio: 0x0, Ct32b1Cap0: 0x1],
+    Pio1_5: [Gpio: 0x0, Ct32b1Cap1: 0x1],
+    Pio1_6: [Gpio: 0x0],
+    Pio1_7: [Gpio: 0x0],
+    Pio1_8: [Gpio: 0x0],
+    Pio1_9: [Gpio: 0x0],
+    Pio1_10: [Gpio: 0x0],
+    Pio1_11: [Gpio: 0x0],
+    Pio1_12: [Gpio: 0x0],
+    Pio1_13: [Gpio: 0x0, Dtr: 0x1, Ct16b0Mat0: 0x2, Txd: 0x3],
+    Pio1_14: [Gpio: 0x0, Dsr: 0x1, Ct16b0Mat1: 0x2, Rxd: 0x3],
+    Pio1_15: [Gpio: 0x0, Dcd: 0x1, Ct16b0Mat2: 0x2, Sck1: 0x3],
+    Pio1_16: [Gpio: 0x0, Ri: 0x1, Ct16b0Cap0: 0x2],
+    Pio1_17: [Gpio: 0x0, Ct16b0Cap1: 0x1, Rxd: 0x2],
+    Pio1_18: [Gpio: 0x0, Ct16b1Cap1: 0x1, Txd: 0x2],
+    Pio1_19: [Gpio: 0x0, Dtr: 0x1, Ssel1: 0x2],
+    Pio1_20: [Gpio: 0x0, Dsr: 0x1, Sck1: 0x2],
+    Pio1_21: [Gpio: 0x0, Dcd: 0x1, Miso1: 0x2],
+    Pio1_22: [Gpio: 0x0, Ri: 0x1, Mosi1: 0x2],
+    Pio1_23: [Gpio: 0x0, Ct16b1Mat1: 0x1, Ssel1: 0x2],
+    Pio1_24: [Gpio: 0x0, Ct32b0Mat0: 0x1],
+    Pio1_25: [Gpio: 0x0, Ct32b0Mat1: 0x1],
+    Pio1_26: [Gpio: 0x0, Ct32b0Mat2: 0x1, Rxd: 0x2],
+    Pio1_27: [Gpio: 0x0, Ct32b0Mat3: 0x1, Txd: 0x2],
+    Pio1_28: [Gpio: 0x0, Ct32b0Cap0: 0x1, Sclk: 0x2],
+    Pio1_29: [Gpio: 0x0, Sck0: 0x1, Ct32b0Cap1: 0x2],
+    Pio1_30: [Gpio: 0x0],
+    Pio1_31: [Gpio: 0x0],
+}
@@ -15,9 +15,38 @@
 
 pub extern crate lpc1347;
 
+#[cfg(feature = "hal")]
+extern crate embedded_hal;
+extern crate nb;
+#[cfg(feature = "hal")]
+extern crate void;
+
+pub use error::Error;
+
+/// Errors reported by the drivers
+pub mod error;
+
 /// Provides support for using the GPIO
 pub mod gpio;
 
+/// Typed I/O pin configuration
+pub mod iocon;
+
+/// Generic driver for the 16-bit and 32-bit timers
+pub mod timer;
+
+/// PWM with the frequency and duty cycle in physical units
+pub mod pwm;
+
+/// 64-bit monotonic clock on a 32-bit timer
+pub mod monotonic;
+
+/// Software timers on a monotonic clock
+pub mod wheel;
+
+/// Frequency and duty cycle measurement on a capture input
+pub mod meter;
+
 /// 16-bit timers
 pub mod timers16;
 
@@ -27,15 +56,21 @@ pub mod timers32;
 /// Analog-digital converter
 pub mod adc;
 
+/// ADC oversampling, filtering and conversion to millivolts
+pub mod filter;
+
 /// Power configuration
 pub mod power;
 
 /// Clock configuration
 pub mod clock;
 
-/// Repetetive-intterupt timer
+/// Repetitive interrupt timer
 pub mod ritimer;
 
+/// SysTick with a periodic tick and blocking delays
+pub mod systick;
+
 /// In-application programming NOTE: untested
 pub mod iap;
 
@@ -0,0 +1,298 @@
+#![allow(dead_code)]
+extern crate lpc1347;
+
+use error::Error;
+use timer::{CapturePin, CaptureReg, Edge, Instance, MatchReg, Timer, Width};
+
+/// A measurement of `FrequencyMeter`, averaged over the gate
+#[derive(Copy, Clone, Debug)]
+pub struct Measurement {
+    cycles: u32,
+    ticks: u64,
+    high: u64,
+    tick_hz: u32,
+}
+
+impl Measurement {
+    /// Get the number of complete cycles in the gate
+    pub fn cycles(&self) -> u32 {
+        self.cycles
+    }
+
+    /// Get the frequency in Hz, zero if the signal stopped
+    pub fn frequency(&self) -> u32 {
+        ((self.frequency_millihertz() + 500) / 1000) as u32
+    }
+
+    /// Get the frequency in mHz, for comparing clocks
+    pub fn frequency_millihertz(&self) -> u64 {
+        if self.ticks == 0 {
+            return 0;
+        }
+        u64::from(self.cycles) * u64::from(self.tick_hz) * 1000 / self.ticks
+    }
+
+    /// Get the average period in timer ticks, zero if the signal stopped
+    pub fn period(&self) -> u64 {
+        if self.cycles == 0 {
+            return 0;
+        }
+        self.ticks / u64::from(self.cycles)
+    }
+
+    /// Get the part of the period that the signal is high in percent
+    pub fn duty_percent(&self) -> u8 {
+        if self.ticks == 0 {
+            return 0;
+        }
+        (self.high * 100 / self.ticks) as u8
+    }
+}
+
+/// Frequency and duty cycle meter on a capture input
+///
+/// The timer runs freely, MR0 counts its overflows so that the timestamps do
+/// not wrap, and MR1 ends gates in which the signal stopped. The capture
+/// switches between rising and falling edges, to time the period and the high
+/// time of each cycle. Every gate that ends gives a `Measurement`, which
+/// averages the cycles in the gate. The gate has to be longer than the
+/// period of the signal, and the high and low times longer than the
+/// interrupt latency.
+///
+/// # Example
+/// ```
+/// // Tachometer on CT16B1_CAP0 (pio0_20), with a 250 ms gate
+/// let timer = Timer::new(p.device.CT16B1, &p.device.SYSCON);
+/// let meter = FrequencyMeter::new(timer, pins.pio0_20.into_capture(), 250,
+///                                 clock::get_system_clock(), &mut p.core.NVIC).unwrap();
+///
+/// // In the CT16B1 task, which owns `meter`
+/// if r.METER.on_interrupt() {
+///     let rpm = r.METER.get_measurement().unwrap().frequency() * 60;
+/// }
+/// ```
+pub struct FrequencyMeter<T> {
+    timer: Timer<T>,
+    cap: CaptureReg,
+    tick_hz: u32,
+    gate: u64,
+    wraps: u64,
+    rising: bool,
+    start: u64,
+    first: Option<u64>,
+    rise: u64,
+    high: u64,
+    cycles: u32,
+    cycles_high: u64,
+    measurement: Option<Measurement>,
+}
+
+impl<T: Instance> FrequencyMeter<T> {
+    /// Route the pin to the capture input and start measuring
+    ///
+    /// Returns `Error::InvalidPeriod` if the gate is zero. The timer counts on
+    /// the undivided peripheral clock for the best resolution.
+    ///
+    /// # Arguments
+    /// * `pin` - The capture pin of the signal, see `into_capture`
+    /// * `gate_ms` - The gate time in milliseconds
+    /// * `pclk` - The peripheral clock of the timer in Hz
+    pub fn new<P: CapturePin<T>>(
+        mut timer: Timer<T>,
+        pin: P,
+        gate_ms: u32,
+        pclk: u32,
+        nvic: &mut lpc1347::NVIC,
+    ) -> Result<Self, Error> {
+        if gate_ms == 0 {
+            return Err(Error::InvalidPeriod);
+        }
+
+        let cap = P::CAPTURE;
+        timer.set_enabled(false);
+        timer.reset();
+        timer.set_timer_mode();
+        timer.set_prescaler(T::Count::from_bits(0));
+        timer.set_capture_pin(pin);
+
+        // Overflows, counted one count ahead of the wrap
+        timer.set_match(MatchReg::Reg0, T::Count::MAX);
+        timer.set_interrupt(MatchReg::Reg0, true, false, false);
+        timer.clear_interrupt(MatchReg::Reg0);
+
+        let mut meter = FrequencyMeter {
+            timer,
+            cap,
+            tick_hz: pclk,
+            gate: u64::from(gate_ms) * u64::from(pclk) / 1000,
+            wraps: 0,
+            rising: true,
+            start: 0,
+            first: None,
+            rise: 0,
+            high: 0,
+            cycles: 0,
+            cycles_high: 0,
+            measurement: None,
+        };
+
+        meter.timer.set_capture(cap, Edge::Rising, true, false)?;
+        meter.timer.clear_capture_interrupt(cap);
+        meter.arm(0);
+        meter.timer.listen(nvic);
+        meter.timer.set_enabled(true);
+
+        Ok(meter)
+    }
+
+    /// Stop measuring and release the timer
+    pub fn free(mut self, nvic: &mut lpc1347::NVIC) -> Timer<T> {
+        self.timer.set_enabled(false);
+        self.timer.unlisten(nvic);
+        self.timer.disable_capture(self.cap);
+        self.timer
+            .set_interrupt(MatchReg::Reg0, false, false, false);
+        self.timer
+            .set_interrupt(MatchReg::Reg1, false, false, false);
+        self.timer
+    }
+
+    /// Get the measurement of the latest gate
+    pub fn get_measurement(&self) -> Option<Measurement> {
+        self.measurement
+    }
+
+    /// Handle edges and overflows, call this from the timer interrupt
+    ///
+    /// Returns true if a gate ended with a new measurement.
+    pub fn on_interrupt(&mut self) -> bool {
+        let mut measured = false;
+
+        if self.timer.is_capture_pending(self.cap) {
+            self.timer.clear_capture_interrupt(self.cap);
+            // Sample the overflow after the capture, so that a wrap in between
+            // is seen and `extend` can tell on which side of it the edge was
+            let count = self.timer.get_capture(self.cap).into_bits();
+            let overflow = self.timer.is_pending(MatchReg::Reg0);
+            let time = self.extend(count, overflow);
+            measured |= self.edge(time);
+        }
+
+        if self.timer.is_pending(MatchReg::Reg0) {
+            self.timer.clear_interrupt(MatchReg::Reg0);
+            self.wraps += 1;
+        }
+
+        if self.timer.is_pending(MatchReg::Reg1) {
+            self.timer.clear_interrupt(MatchReg::Reg1);
+            measured |= self.timeout();
+        }
+
+        measured
+    }
+
+    /// Handle a captured edge
+    fn edge(&mut self, time: u64) -> bool {
+        let rising = self.rising;
+        self.rising = !rising;
+        let edge = if self.rising {
+            Edge::Rising
+        } else {
+            Edge::Falling
+        };
+        let _ = self.timer.set_capture(self.cap, edge, true, false);
+
+        if !rising {
+            // Only count the high time of cycles that complete
+            self.high = time - self.rise;
+            return false;
+        }
+
+        let mut measured = false;
+        match self.first {
+            Some(first) => {
+                self.cycles += 1;
+                self.cycles_high += self.high;
+
+                if time - first >= self.gate {
+                    self.publish(time - first);
+                    self.first = Some(time);
+                    self.arm(time);
+                    measured = true;
+                }
+            }
+            None => {
+                self.first = Some(time);
+                self.arm(time);
+            }
+        }
+
+        self.rise = time;
+        self.high = 0;
+        measured
+    }
+
+    /// End a gate in which the signal stopped
+    fn timeout(&mut self) -> bool {
+        let now = self.now();
+        if now - self.start < 2 * self.gate {
+            // The gate is longer than the counter, wait for the next match
+            return false;
+        }
+
+        let ticks = match self.first {
+            Some(first) if self.cycles > 0 => self.rise - first,
+            _ => 0,
+        };
+        self.publish(ticks);
+        self.first = None;
+        self.arm(now);
+        true
+    }
+
+    /// Store the measurement of a gate and start the next one
+    fn publish(&mut self, ticks: u64) {
+        self.measurement = Some(Measurement {
+            cycles: if ticks == 0 { 0 } else { self.cycles },
+            ticks,
+            high: self.cycles_high,
+            tick_hz: self.tick_hz,
+        });
+        self.cycles = 0;
+        self.cycles_high = 0;
+    }
+
+    /// Start a gate, MR1 interrupts when it ends
+    fn arm(&mut self, start: u64) {
+        self.start = start;
+
+        // The edge that closes the gate may be late, so allow one more gate
+        let end = start + 2 * self.gate;
+        let count = end & u64::from(T::Count::MAX.into_bits());
+        self.timer
+            .set_match(MatchReg::Reg1, T::Count::from_bits(count as u32));
+        self.timer.clear_interrupt(MatchReg::Reg1);
+        self.timer.set_interrupt(MatchReg::Reg1, true, false, false);
+    }
+
+    /// Get the current time in timer ticks
+    fn now(&self) -> u64 {
+        let count = self.timer.get_counter().into_bits();
+        let overflow = self.timer.is_pending(MatchReg::Reg0);
+        self.extend(count, overflow)
+    }
+
+    /// Extend a count to 64 bits
+    ///
+    /// An overflow that is pending but not yet counted belongs to the count if
+    /// the count is in the lower half, i.e. after the wrap.
+    fn extend(&self, count: u32, overflow: bool) -> u64 {
+        let range = u64::from(T::Count::MAX.into_bits()) + 1;
+        let wraps = if overflow && u64::from(count) < range / 2 {
+            self.wraps + 1
+        } else {
+            self.wraps
+        };
+        wraps * range + u64::from(count)
+    }
+}
@@ -0,0 +1,253 @@
+#![allow(dead_code)]
+extern crate lpc1347;
+
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+use error::Error;
+use timer::{Instance, MatchReg, Timer};
+
+/// Rate of the monotonic clock, one tick per microsecond
+const TICK_HZ: u32 = 1_000_000;
+
+/// Counter value halfway through the range of the timer, see `Monotonic`
+const HALF: u32 = 0x8000_0000;
+
+/// A point in time of the monotonic clock, in microseconds since it started
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Instant {
+    micros: u64,
+}
+
+impl Instant {
+    /// Create an instant from microseconds since the clock started
+    pub fn from_micros(micros: u64) -> Self {
+        Instant { micros }
+    }
+
+    /// Get the microseconds since the clock started
+    pub fn as_micros(&self) -> u64 {
+        self.micros
+    }
+
+    /// Get the time passed since an earlier instant, zero if it is later
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        Duration::from_micros(self.micros.saturating_sub(earlier.micros))
+    }
+
+    /// Go back in time, `None` if that is before the clock started
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.micros.checked_sub(micros(duration)).map(Instant::from_micros)
+    }
+}
+
+/// Convert a duration to whole microseconds
+fn micros(duration: Duration) -> u64 {
+    duration.as_secs() * u64::from(TICK_HZ) + u64::from(duration.subsec_micros())
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, rhs: Duration) -> Instant {
+        Instant::from_micros(self.micros + micros(rhs))
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+/// Stops at the start of the clock, see `Instant::checked_sub`
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, rhs: Duration) -> Instant {
+        Instant::from_micros(self.micros.saturating_sub(micros(rhs)))
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Instant) -> Duration {
+        self.duration_since(rhs)
+    }
+}
+
+/// A 32-bit timer that can drive `Monotonic`
+pub trait Source: Instance<Count = u32> {
+    /// Half periods of the counter that have passed
+    fn periods() -> &'static AtomicUsize;
+
+    /// Read the timer counter without owning the timer
+    fn counter() -> u32;
+}
+
+/// Implements `Source` for the 32-bit timers
+macro_rules! sources {
+    ($($CT: ident: $PERIODS: ident,)+) => {
+        $(
+            static $PERIODS: AtomicUsize = AtomicUsize::new(0);
+
+            impl Source for lpc1347::$CT {
+                fn periods() -> &'static AtomicUsize {
+                    &$PERIODS
+                }
+
+                fn counter() -> u32 {
+                    // Reading TC has no side effects
+                    unsafe { (*lpc1347::$CT::ptr()).tc.read().bits() }
+                }
+            }
+        )+
+    };
+}
+
+sources! {
+    CT32B0: CT32B0_PERIODS,
+    CT32B1: CT32B1_PERIODS,
+}
+
+/// 64-bit microsecond clock on a free-running 32-bit timer
+///
+/// The timer counts microseconds, and MR0 and MR1 interrupt halfway through
+/// and at the end of the counter range to extend it to 64 bits. `now` only
+/// reads the counter and an atomic count of half periods, so it can be called
+/// from any priority, while `on_interrupt` has to be called from the timer
+/// interrupt at least once every 35 minutes. MR2 serves as an alarm, see
+/// `set_alarm`, and MR3 remains available.
+///
+/// # Example
+/// ```
+/// // In init
+/// let timer = Timer::new(p.device.CT32B0, &p.device.SYSCON);
+/// let clock = Monotonic::new(timer, clock::get_system_clock(), &mut p.core.NVIC).unwrap();
+///
+/// // In the CT32B0 task, which owns `clock`
+/// r.CLOCK.on_interrupt();
+///
+/// // In any task
+/// let start = Monotonic::<CT32B0>::now();
+/// let elapsed = Monotonic::<CT32B0>::now() - start;
+/// ```
+pub struct Monotonic<T> {
+    timer: Timer<T>,
+    alarm: bool,
+}
+
+impl<T: Source> Monotonic<T> {
+    /// Start the clock from zero
+    ///
+    /// Returns `Error::InvalidFrequency` if the peripheral clock is below
+    /// 1 MHz. The clock runs slow if `pclk` is not a multiple of 1 MHz.
+    ///
+    /// # Arguments
+    /// * `pclk` - The peripheral clock of the timer in Hz
+    pub fn new(mut timer: Timer<T>, pclk: u32, nvic: &mut lpc1347::NVIC) -> Result<Self, Error> {
+        if pclk < TICK_HZ {
+            return Err(Error::InvalidFrequency);
+        }
+
+        timer.set_enabled(false);
+        timer.reset();
+        timer.set_prescaler(pclk / TICK_HZ - 1);
+
+        // The matches fire on the last count before each half, `now` compensates
+        timer.set_match(MatchReg::Reg0, HALF - 1);
+        timer.set_match(MatchReg::Reg1, 0xFFFF_FFFF);
+        timer.set_interrupt(MatchReg::Reg0, true, false, false);
+        timer.set_interrupt(MatchReg::Reg1, true, false, false);
+        timer.clear_interrupt(MatchReg::Reg0);
+        timer.clear_interrupt(MatchReg::Reg1);
+
+        T::periods().store(0, Ordering::SeqCst);
+        timer.listen(nvic);
+        timer.set_enabled(true);
+
+        Ok(Monotonic {
+            timer,
+            alarm: false,
+        })
+    }
+
+    /// Stop the clock and release the timer
+    pub fn free(mut self, nvic: &mut lpc1347::NVIC) -> Timer<T> {
+        self.timer.set_enabled(false);
+        self.timer.unlisten(nvic);
+        self.timer
+            .set_interrupt(MatchReg::Reg0, false, false, false);
+        self.timer
+            .set_interrupt(MatchReg::Reg1, false, false, false);
+        self.clear_alarm();
+        self.timer
+    }
+
+    /// Count the passed half periods, call this from the timer interrupt
+    ///
+    /// Returns true if the alarm went off, which also clears it.
+    pub fn on_interrupt(&mut self) -> bool {
+        for mr in &[MatchReg::Reg0, MatchReg::Reg1] {
+            if self.timer.is_pending(*mr) {
+                self.timer.clear_interrupt(*mr);
+                T::periods().fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        if self.alarm && self.timer.is_pending(MatchReg::Reg2) {
+            self.clear_alarm();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Interrupt when the counter reaches an instant, using MR2
+    ///
+    /// Only the lower 32 bits of the instant are matched, so an alarm more
+    /// than 71 minutes ahead goes off early, and an alarm that has already
+    /// passed goes off after the counter wraps. Compare with `now` to tell.
+    pub fn set_alarm(&mut self, at: Instant) {
+        // The lower bits of an instant are the counter plus one, see `now`
+        let counter = (at.as_micros() as u32).wrapping_sub(1);
+        self.timer.set_match(MatchReg::Reg2, counter);
+        self.timer.clear_interrupt(MatchReg::Reg2);
+        self.timer.set_interrupt(MatchReg::Reg2, true, false, false);
+        self.alarm = true;
+    }
+
+    /// Cancel the alarm
+    pub fn clear_alarm(&mut self) {
+        self.timer
+            .set_interrupt(MatchReg::Reg2, false, false, false);
+        self.timer.clear_interrupt(MatchReg::Reg2);
+        self.alarm = false;
+    }
+
+    /// Get the current time
+    pub fn now() -> Instant {
+        let periods = T::periods().load(Ordering::SeqCst) as u64;
+
+        // Offset by one count, so that the halves line up with the matches
+        let counter = T::counter().wrapping_add(1);
+
+        // The top bit of the counter follows the parity of the half periods,
+        // unless a half has passed without being counted yet. Flipping it makes
+        // up for that.
+        let counter = counter ^ ((periods as u32 & 1) << 31);
+        Instant::from_micros((periods << 31) + u64::from(counter))
+    }
+
+    /// Get the time passed since an instant
+    pub fn elapsed(since: Instant) -> Duration {
+        Self::now().duration_since(since)
+    }
+}
@@ -3,6 +3,9 @@ extern crate cortex_m;
 extern crate cortex_m_rtfm as rtfm;
 extern crate lpc1347;
 
+use error::Error;
+use gpio::{Pin, Port};
+use iocon;
 use lpc1347::Interrupt::{
     GINT0, GINT1, PIN_INT0, PIN_INT1, PIN_INT2, PIN_INT3, PIN_INT4, PIN_INT5, PIN_INT6, PIN_INT7,
     BOD_IRQ, USBWAKEUP, WWDT,
@@ -255,3 +258,154 @@ pub fn set_wakeup_interrupt(
         }
     }
 }
+
+/// Pins that exist on each port
+const PORT_PINS: [u32; 2] = [0x00FF_FFFF, 0xFFFF_FFFF];
+
+/// Pins that `park_pins` leaves alone
+///
+/// Keep the pins that act as wake sources, and the outputs that must hold
+/// their level while sleeping.
+#[derive(Copy, Clone)]
+pub struct KeepPins {
+    mask: [u32; 2],
+}
+
+impl KeepPins {
+    /// Park every pin
+    pub fn new() -> Self {
+        KeepPins { mask: [0, 0] }
+    }
+
+    /// Keep the reset and SWD pins (pio0_0, pio0_10 and pio0_15)
+    pub fn debug(self) -> Self {
+        self.mark(Port::Port0, 0)
+            .mark(Port::Port0, 10)
+            .mark(Port::Port0, 15)
+    }
+
+    /// Keep a pin by port and position
+    ///
+    /// Returns `Error::InvalidPin` if the port has no pin at the position.
+    pub fn keep(self, port: Port, bitpos: u32) -> Result<Self, Error> {
+        if bitpos >= 32 || PORT_PINS[port as usize] & (1 << bitpos) == 0 {
+            return Err(Error::InvalidPin);
+        }
+        Ok(self.mark(port, bitpos))
+    }
+
+    /// Keep a typed pin
+    pub fn keep_pin<P: Pin>(self, _pin: &P) -> Self {
+        self.mark(P::PORT, P::BITPOS)
+    }
+
+    /// Keep a pin that is known to exist
+    fn mark(mut self, port: Port, bitpos: u32) -> Self {
+        self.mask[port as usize] |= 1 << bitpos;
+        self
+    }
+}
+
+impl Default for KeepPins {
+    fn default() -> Self {
+        KeepPins::new()
+    }
+}
+
+/// Pin state saved by `park_pins`, restored by `restore_pins`
+pub struct ParkedPins {
+    iocon: [[u32; 32]; 2],
+    dir: [u32; 2],
+    value: [u32; 2],
+    parked: [u32; 2],
+}
+
+/// Park the unused pins before entering a low-power mode
+///
+/// Following the IDD measurement conditions of the datasheet, every pin that
+/// is not kept is switched to GPIO with the pull resistors disabled, and driven
+/// low. The IOCON registers and the GPIO direction and output value of these
+/// pins are saved, so that `restore_pins` can bring them back exactly after
+/// wake-up.
+///
+/// Note that parking pio0_0 disables the reset pin and parking pio0_10 and
+/// pio0_15 disables SWD until the pins are restored, see `KeepPins::debug`.
+///
+/// # Arguments
+/// * `keep` - The pins to leave alone
+///
+/// # Example
+/// ```
+/// // Park everything except the wake-up button on pio0_1 and the debugger
+/// let keep = power::KeepPins::new().debug().keep(Port::Port0, 1).unwrap();
+/// let parked = power::park_pins(&p.device.IOCON, &p.device.GPIO_PORT, keep);
+///
+/// power::power_down(&p.device.PMU, &p.device.SYSCON, &mut p.core.SCB, false, true);
+/// wfi();
+///
+/// power::restore_pins(&p.device.IOCON, &p.device.GPIO_PORT, parked);
+/// ```
+pub fn park_pins(
+    iocon: &lpc1347::IOCON,
+    gpio_port: &lpc1347::gpio_port::RegisterBlock,
+    keep: KeepPins,
+) -> ParkedPins {
+    let mut parked = ParkedPins {
+        iocon: [[0; 32]; 2],
+        dir: [0; 2],
+        value: [0; 2],
+        parked: [0; 2],
+    };
+
+    for (index, port) in [Port::Port0, Port::Port1].iter().enumerate() {
+        let pins = PORT_PINS[index] & !keep.mask[index];
+
+        for bitpos in 0..32 {
+            if pins & (1 << bitpos) != 0 {
+                if let Ok(value) = iocon::read(iocon, *port, bitpos) {
+                    parked.iocon[index][bitpos as usize] = value;
+                    let _ = iocon::park(iocon, *port, bitpos);
+                }
+            }
+        }
+
+        // SET reads back the output value, PIN would return the pin level
+        parked.dir[index] = gpio_port.dir[index].read().bits();
+        parked.value[index] = gpio_port.set[index].read().bits();
+        parked.parked[index] = pins;
+
+        unsafe {
+            gpio_port.clr[index].write(|w| w.bits(pins));
+            gpio_port.dir[index].modify(|r, w| w.bits(r.bits() | pins));
+        }
+    }
+
+    parked
+}
+
+/// Restore the pins saved by `park_pins` after wake-up
+///
+/// Pins that were kept are not touched.
+pub fn restore_pins(
+    iocon: &lpc1347::IOCON,
+    gpio_port: &lpc1347::gpio_port::RegisterBlock,
+    parked: ParkedPins,
+) {
+    for (index, port) in [Port::Port0, Port::Port1].iter().enumerate() {
+        let pins = parked.parked[index];
+
+        // Restore the output values before the pins are driven again
+        unsafe {
+            gpio_port.set[index].write(|w| w.bits(parked.value[index] & pins));
+            gpio_port.clr[index].write(|w| w.bits(!parked.value[index] & pins));
+            gpio_port.dir[index]
+                .modify(|r, w| w.bits((r.bits() & !pins) | (parked.dir[index] & pins)));
+        }
+
+        for bitpos in 0..32 {
+            if pins & (1 << bitpos) != 0 {
+                let _ = iocon::write(iocon, *port, bitpos, parked.iocon[index][bitpos as usize]);
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+#![allow(dead_code)]
+extern crate cortex_m;
+extern crate lpc1347;
+
+use error::Error;
+use timer::{Control, Instance, MatchPin, MatchReg, Timer, Width};
+
+/// PWM output with the frequency and duty cycle in physical units
+///
+/// The period is kept in MR3, which resets the counter, so MR0-MR2 are the
+/// channels. Route the channels to their pins with `set_pin`. Duty cycle
+/// changes that would cut the current pulse short are deferred to the end of
+/// the period, which needs `listen` and `on_interrupt`.
+///
+/// # Example
+/// ```
+/// // 20 kHz motor PWM at 30% on CT32B0_MAT0 (pio0_18)
+/// let timer = Timer::new(p.device.CT32B0, &p.device.SYSCON);
+/// let mut pwm = Pwm::new(timer, 20_000, clock::get_system_clock()).unwrap();
+/// pwm.set_pin(pins.pio0_18.into_match_output()).unwrap();
+/// pwm.set_duty_percent(MatchReg::Reg0, 30).unwrap();
+/// pwm.enable(MatchReg::Reg0).unwrap();
+/// pwm.listen(&mut p.core.NVIC);
+///
+/// // In the CT32B0 task, which owns `pwm`
+/// r.PWM.on_interrupt();
+/// ```
+pub struct Pwm<T> {
+    timer: Timer<T>,
+    period: u32,
+    prescaler: u32,
+    pending: [Option<u32>; 3],
+}
+
+impl<T: Instance> Pwm<T> {
+    /// Configure the timer for PWM and start it
+    ///
+    /// Returns `Error::InvalidFrequency` if the timer cannot run at the
+    /// frequency, see `Timer::set_frequency`.
+    ///
+    /// # Arguments
+    /// * `frequency` - The PWM frequency in Hz
+    /// * `pclk` - The peripheral clock of the timer in Hz
+    pub fn new(mut timer: Timer<T>, frequency: u32, pclk: u32) -> Result<Self, Error> {
+        timer.set_enabled(false);
+        timer.reset();
+        let period = timer.set_frequency(MatchReg::Reg3, frequency, pclk)?;
+        let prescaler = timer.get_prescaler().into_bits();
+
+        let mut pwm = Pwm {
+            timer,
+            period,
+            prescaler,
+            pending: [None; 3],
+        };
+
+        // Start all channels low
+        for channel in &[MatchReg::Reg0, MatchReg::Reg1, MatchReg::Reg2] {
+            pwm.timer.set_match(*channel, T::Count::from_bits(period));
+        }
+        pwm.timer.set_enabled(true);
+
+        Ok(pwm)
+    }
+
+    /// Stop the timer and release it
+    pub fn free(mut self) -> Timer<T> {
+        self.timer.set_enabled(false);
+        self.timer
+            .set_interrupt(MatchReg::Reg3, false, false, false);
+        self.timer
+    }
+
+    /// Get the frequency that was achieved in Hz
+    ///
+    /// # Arguments
+    /// * `pclk` - The peripheral clock of the timer in Hz
+    pub fn get_frequency(&self, pclk: u32) -> u32 {
+        pclk / (self.period * (self.prescaler + 1))
+    }
+
+    /// Enable the timer interrupt, which applies deferred duty cycle changes
+    pub fn listen(&mut self, nvic: &mut lpc1347::NVIC) {
+        self.timer.listen(nvic);
+    }
+
+    /// Disable the timer interrupt
+    pub fn unlisten(&mut self, nvic: &mut lpc1347::NVIC) {
+        self.timer.unlisten(nvic);
+    }
+
+    /// Apply deferred duty cycle changes, call this from the timer interrupt
+    ///
+    /// Returns true if the period ended.
+    pub fn on_interrupt(&mut self) -> bool {
+        if !self.timer.is_pending(MatchReg::Reg3) {
+            return false;
+        }
+        self.timer.clear_interrupt(MatchReg::Reg3);
+
+        // The counter was just reset, so every match is still ahead of it
+        for (index, channel) in [MatchReg::Reg0, MatchReg::Reg1, MatchReg::Reg2]
+            .iter()
+            .enumerate()
+        {
+            if let Some(value) = self.pending[index].take() {
+                self.timer.set_match(*channel, T::Count::from_bits(value));
+            }
+        }
+        self.timer.set_interrupt(MatchReg::Reg3, false, true, false);
+        true
+    }
+
+    /// Get the number of duty cycle steps, i.e. the period in timer ticks
+    pub fn get_max_duty(&self) -> u32 {
+        self.period
+    }
+
+    /// Hand a pin converted with `into_match_output` over to its channel
+    ///
+    /// Pins driven by MR3 return `Error::InvalidChannel`, as MR3 holds the
+    /// period.
+    pub fn set_pin<P: MatchPin<T>>(&mut self, pin: P) -> Result<(), Error> {
+        Self::check(P::MATCH)?;
+        self.timer.set_match_pin(pin);
+        Ok(())
+    }
+
+    /// Switch a channel to PWM output
+    ///
+    /// MR3 holds the period and returns `Error::InvalidChannel`.
+    pub fn enable(&mut self, channel: MatchReg) -> Result<(), Error> {
+        Self::check(channel)?;
+        self.timer
+            .set_external_match(channel, Control::Nothing, false);
+        self.timer.set_pwm(channel, true);
+        Ok(())
+    }
+
+    /// Switch a channel back to a regular match output, which is low
+    pub fn disable(&mut self, channel: MatchReg) -> Result<(), Error> {
+        Self::check(channel)?;
+        self.timer.set_pwm(channel, false);
+        Ok(())
+    }
+
+    /// Set the duty cycle as a fraction
+    ///
+    /// # Arguments
+    /// * `channel` - MR0-MR2
+    /// * `numerator` - High time, limited to `denominator`
+    /// * `denominator` - The full period, must not be zero
+    ///
+    /// # Example
+    /// ```
+    /// // A third of the period high
+    /// pwm.set_duty(MatchReg::Reg1, 1, 3).unwrap();
+    /// ```
+    pub fn set_duty(
+        &mut self,
+        channel: MatchReg,
+        numerator: u32,
+        denominator: u32,
+    ) -> Result<(), Error> {
+        if denominator == 0 {
+            return Err(Error::InvalidDuty);
+        }
+
+        let numerator = ::core::cmp::min(numerator, denominator);
+        let ticks = u64::from(self.period) * u64::from(numerator) / u64::from(denominator);
+        self.set_duty_ticks(channel, ticks as u32)
+    }
+
+    /// Set the duty cycle in percent, limited to 100
+    pub fn set_duty_percent(&mut self, channel: MatchReg, percent: u8) -> Result<(), Error> {
+        self.set_duty(channel, u32::from(percent), 100)
+    }
+
+    /// Set the high time in timer ticks, limited to `get_max_duty`
+    ///
+    /// The match register is written right away when that does not cut the
+    /// current period short, otherwise at the end of the period by
+    /// `on_interrupt`, so the output never skips a pulse.
+    pub fn set_duty_ticks(&mut self, channel: MatchReg, ticks: u32) -> Result<(), Error> {
+        Self::check(channel)?;
+
+        // The output goes high on match and low again when MR3 resets the counter
+        let new = self.period - ::core::cmp::min(ticks, self.period);
+        let old = self.timer.get_match(channel).into_bits();
+        let index = channel as usize;
+
+        cortex_m::interrupt::free(|_| {
+            // Moving the match below a counter that has not reached the old
+            // match yet would keep the output low for the rest of the period
+            let counter = self.timer.get_counter().into_bits();
+            if self.timer.is_enabled() && new <= counter && counter < old {
+                self.pending[index] = Some(new);
+                self.timer.set_interrupt(MatchReg::Reg3, true, true, false);
+            } else {
+                self.pending[index] = None;
+                self.timer.set_match(channel, T::Count::from_bits(new));
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Get the high time of a channel in timer ticks, including deferred changes
+    pub fn get_duty_ticks(&self, channel: MatchReg) -> u32 {
+        let value = match self.pending.get(channel as usize) {
+            Some(&Some(value)) => value,
+            _ => self.timer.get_match(channel).into_bits(),
+        };
+        self.period.saturating_sub(value)
+    }
+
+    /// Only MR0-MR2 are channels, MR3 holds the period
+    fn check(channel: MatchReg) -> Result<(), Error> {
+        match channel {
+            MatchReg::Reg3 => Err(Error::InvalidChannel),
+            _ => Ok(()),
+        }
+    }
+}
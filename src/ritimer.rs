@@ -1,24 +1,54 @@
 #![allow(dead_code)]
 
 extern crate lpc1347;
+use error::Error;
 use lpc1347::Interrupt::RIT_IRQ;
 
+/// The counter, compare and mask registers are 48 bits wide
+const MAX: u64 = 0xFFFF_FFFF_FFFF;
+
 /// Initialize the timer
-fn init(rit: &lpc1347::RITIMER, clear: bool, debug: bool) {
+///
+/// The timer counts on the system clock, and interrupts when the counter
+/// matches the compare value in the bits that are not masked.
+///
+/// # Arguments
+/// * `clear` - Clear the counter on a match, which makes the timer periodic
+/// * `debug` - Halt the timer while the processor is halted by a debugger
+///
+/// # Example
+/// ```
+/// // Interrupt every time the lower 24 bits of the counter are zero
+/// ritimer::init(&p.device.RITIMER, false, true);
+/// ritimer::set_compare(&p.device.RITIMER, 0);
+/// ritimer::set_mask(&p.device.RITIMER, 0xFFFF_FF00_0000);
+/// ritimer::set_enabled(&p.device.RITIMER, &mut p.core.NVIC, true);
+/// ```
+pub fn init(rit: &lpc1347::RITIMER, clear: bool, debug: bool) {
+    set_clear_on_match(rit, clear);
+    set_debug_halt(rit, debug);
+}
+
+/// Clear the counter when it matches, or let it run on
+pub fn set_clear_on_match(rit: &lpc1347::RITIMER, clear: bool) {
     rit.ctrl.modify(|_, w| w.ritenclr().bit(clear));
-    rit.ctrl.modify(|_, w| w.ritenbr().bit(debug));
+}
+
+/// Halt the timer while the processor is halted by a debugger, or keep counting
+pub fn set_debug_halt(rit: &lpc1347::RITIMER, halt: bool) {
+    rit.ctrl.modify(|_, w| w.ritenbr().bit(halt));
 }
 
 /// Reset the timer
-fn reset(rit: &lpc1347::RITIMER) {
+pub fn reset(rit: &lpc1347::RITIMER) {
     unsafe {
         rit.counter.modify(|_, w| w.ricounter().bits(0u32));
         rit.counter_h.modify(|_, w| w.ricounter().bits(0u16));
     }
 }
 
-/// Turn the timer on or off
-fn set_enabled(rit: &lpc1347::RITIMER, nvic: &mut lpc1347::NVIC, enable: bool) {
+/// Turn the timer and its interrupt on or off
+pub fn set_enabled(rit: &lpc1347::RITIMER, nvic: &mut lpc1347::NVIC, enable: bool) {
     rit.ctrl.modify(|_, w| w.riten().bit(enable));
     if enable {
         nvic.enable(RIT_IRQ);
@@ -27,18 +57,89 @@ fn set_enabled(rit: &lpc1347::RITIMER, nvic: &mut lpc1347::NVIC, enable: bool) {
     }
 }
 
-/// Set the comparison bits
-fn set_compare(rit: &lpc1347::RITIMER, value: u64) {
+/// Interrupt periodically
+///
+/// Sets the compare value from the tick rate, clears the mask, and enables
+/// clear-on-match. Returns `Error::InvalidFrequency` if the rate is zero or
+/// above the system clock.
+///
+/// # Arguments
+/// * `frequency` - The tick rate in Hz
+/// * `system_clock` - The frequency of the system clock in Hz
+///
+/// # Example
+/// ```
+/// // 1 kHz ticks
+/// ritimer::set_frequency(&p.device.RITIMER, 1000, clock::get_system_clock()).unwrap();
+/// ritimer::set_enabled(&p.device.RITIMER, &mut p.core.NVIC, true);
+/// ```
+pub fn set_frequency(
+    rit: &lpc1347::RITIMER,
+    frequency: u32,
+    system_clock: u32,
+) -> Result<(), Error> {
+    if frequency == 0 || frequency > system_clock {
+        return Err(Error::InvalidFrequency);
+    }
+
+    set_compare(rit, u64::from(system_clock / frequency));
+    set_mask(rit, 0);
+    set_clear_on_match(rit, true);
+    reset(rit);
+
+    Ok(())
+}
+
+/// Set the comparison bits, only the lower 48 bits are used
+pub fn set_compare(rit: &lpc1347::RITIMER, value: u64) {
+    let value = value & MAX;
     unsafe {
-        rit.compval.modify(|_, w| w.ricomp().bits((value & 0xFFFF_FFFF) as u32));
-        rit.compval_h.modify(|_, w| w.ricomp().bits(((value >> 16) & 0x0000_FFFF) as u16));
+        rit.compval
+            .modify(|_, w| w.ricomp().bits((value & 0xFFFF_FFFF) as u32));
+        rit.compval_h
+            .modify(|_, w| w.ricomp().bits((value >> 32) as u16));
     }
 }
 
-/// Set the mask bits
-fn set_mask(rit: &lpc1347::RITIMER, value: u64) {
+/// Set the mask bits, only the lower 48 bits are used
+///
+/// The compare value always matches in the bits that are set.
+pub fn set_mask(rit: &lpc1347::RITIMER, value: u64) {
+    let value = value & MAX;
     unsafe {
-        rit.mask.modify(|_, w| w.rimask().bits((value & 0xFFFF_FFFF) as u32));
-        rit.mask_h.modify(|_, w| w.rimask().bits(((value >> 16) & 0x0000_FFFF) as u16));
+        rit.mask
+            .modify(|_, w| w.rimask().bits((value & 0xFFFF_FFFF) as u32));
+        rit.mask_h
+            .modify(|_, w| w.rimask().bits((value >> 32) as u16));
+    }
+}
+
+/// Get the current count
+pub fn get_counter(rit: &lpc1347::RITIMER) -> u64 {
+    // Read the upper half again if the lower half wrapped in between
+    loop {
+        let high = rit.counter_h.read().bits();
+        let low = rit.counter.read().bits();
+        if rit.counter_h.read().bits() == high {
+            return u64::from(high) << 32 | u64::from(low);
+        }
     }
 }
+
+/// Determine if the interrupt is pending
+pub fn is_pending(rit: &lpc1347::RITIMER) -> bool {
+    rit.ctrl.read().ritint().bit_is_set()
+}
+
+/// Clear the interrupt, call this from the `RIT_IRQ` task
+///
+/// # Example
+/// ```
+/// fn rit_tick(_t: &mut Threshold, r: RIT_IRQ::Resources) {
+///     ritimer::clear_interrupt(&r.RITIMER);
+/// }
+/// ```
+pub fn clear_interrupt(rit: &lpc1347::RITIMER) {
+    // The flag is cleared by writing a one
+    rit.ctrl.modify(|_, w| w.ritint().bit(true));
+}
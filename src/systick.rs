@@ -0,0 +1,184 @@
+#![allow(dead_code)]
+extern crate cortex_m;
+extern crate lpc1347;
+
+use self::cortex_m::peripheral::syst::SystClkSource;
+use error::Error;
+use lpc1347::SYST;
+
+/// The reload register is 24 bits wide
+const MAX_RELOAD: u32 = 0x00FF_FFFF;
+
+/// Clock that drives SysTick
+#[derive(Copy, Clone)]
+pub enum ClockSource {
+    /// The processor clock
+    Core,
+    /// The reference clock of the device
+    External,
+}
+
+/// Calibration value of SysTick, see `SysTick::get_calibration`
+#[derive(Copy, Clone, Debug)]
+pub struct Calibration {
+    /// Reload value for 10 ms on the reference clock, zero if unknown
+    pub ticks_per_10ms: u32,
+    /// False if the 10 ms value is rounded
+    pub precise: bool,
+    /// False if there is no reference clock, only the processor clock
+    pub reference: bool,
+}
+
+/// SysTick driver with a periodic tick and blocking delays
+///
+/// The counter always runs, so the delays also work while the tick is used
+/// for the `SYS_TICK` task.
+///
+/// # Example
+/// ```
+/// // 1 kHz SYS_TICK task on the core clock
+/// let mut systick = SysTick::new(p.core.SYST, ClockSource::Core, clock::get_system_clock());
+/// systick.set_rate(1000).unwrap();
+/// systick.listen();
+///
+/// systick.delay_us(50);
+/// ```
+pub struct SysTick {
+    syst: SYST,
+    clock: u32,
+}
+
+impl SysTick {
+    /// Take ownership of SysTick and start it
+    ///
+    /// # Arguments
+    /// * `source` - The clock to count
+    /// * `clock` - The frequency of that clock in Hz
+    pub fn new(mut syst: SYST, source: ClockSource, clock: u32) -> Self {
+        syst.disable_counter();
+        syst.disable_interrupt();
+        syst.set_clock_source(match source {
+            ClockSource::Core => SystClkSource::Core,
+            ClockSource::External => SystClkSource::External,
+        });
+        syst.set_reload(MAX_RELOAD);
+        syst.clear_current();
+        syst.enable_counter();
+
+        SysTick { syst, clock }
+    }
+
+    /// Stop SysTick and release it
+    pub fn free(mut self) -> SYST {
+        self.syst.disable_interrupt();
+        self.syst.disable_counter();
+        self.syst
+    }
+
+    /// Set the rate at which the counter wraps
+    ///
+    /// Returns `Error::InvalidFrequency` if the rate is zero, too fast, or so
+    /// slow that the reload does not fit in 24 bits.
+    ///
+    /// # Arguments
+    /// * `frequency` - The tick rate in Hz
+    pub fn set_rate(&mut self, frequency: u32) -> Result<(), Error> {
+        if frequency == 0 {
+            return Err(Error::InvalidFrequency);
+        }
+
+        let ticks = self.clock / frequency;
+        match ticks {
+            2..=0x0100_0000 => {}
+            _ => return Err(Error::InvalidFrequency),
+        }
+
+        self.syst.set_reload(ticks - 1);
+        self.syst.clear_current();
+        Ok(())
+    }
+
+    /// Get the rate at which the counter wraps in Hz
+    pub fn get_rate(&self) -> u32 {
+        self.clock / (SYST::get_reload() + 1)
+    }
+
+    /// Enable the `SYS_TICK` exception
+    pub fn listen(&mut self) {
+        self.syst.enable_interrupt();
+    }
+
+    /// Disable the `SYS_TICK` exception
+    pub fn unlisten(&mut self) {
+        self.syst.disable_interrupt();
+    }
+
+    /// Determine if the counter wrapped since the last call
+    pub fn has_wrapped(&mut self) -> bool {
+        self.syst.has_wrapped()
+    }
+
+    /// Read the calibration value
+    ///
+    /// On the LPC1347 the value is taken from `SYSTCKCAL` in SYSCON.
+    pub fn get_calibration() -> Calibration {
+        Calibration {
+            ticks_per_10ms: SYST::get_ticks_per_10ms(),
+            precise: SYST::is_precise(),
+            reference: SYST::has_reference_clock(),
+        }
+    }
+
+    /// Wait for some microseconds
+    pub fn delay_us(&mut self, us: u32) {
+        let ticks = u64::from(us) * u64::from(self.clock) / 1_000_000;
+        self.delay_ticks(ticks);
+    }
+
+    /// Wait for some milliseconds
+    pub fn delay_ms(&mut self, ms: u32) {
+        let ticks = u64::from(ms) * u64::from(self.clock) / 1_000;
+        self.delay_ticks(ticks);
+    }
+
+    /// Wait for some clock cycles of the counter
+    fn delay_ticks(&mut self, ticks: u64) {
+        let period = u64::from(SYST::get_reload()) + 1;
+        let mut elapsed = 0;
+        let mut last = SYST::get_current();
+
+        // The counter counts down, add up the steps including wraps
+        while elapsed < ticks {
+            let current = SYST::get_current();
+            elapsed += (u64::from(last) + period - u64::from(current)) % period;
+            last = current;
+        }
+    }
+}
+
+/// Implements the embedded-hal delays for each argument type
+macro_rules! hal_delays {
+    ($($t: ty,)+) => {
+        $(
+            #[cfg(feature = "hal")]
+            impl ::embedded_hal::blocking::delay::DelayUs<$t> for SysTick {
+                fn delay_us(&mut self, us: $t) {
+                    SysTick::delay_us(self, u32::from(us));
+                }
+            }
+
+            #[cfg(feature = "hal")]
+            impl ::embedded_hal::blocking::delay::DelayMs<$t> for SysTick {
+                fn delay_ms(&mut self, ms: $t) {
+                    SysTick::delay_ms(self, u32::from(ms));
+                }
+            }
+        )+
+    };
+}
+
+hal_delays! {
+    u8,
+    u16,
+    u32,
+}
@@ -0,0 +1,798 @@
+#![allow(dead_code)]
+extern crate lpc1347;
+
+use error::Error;
+use gpio;
+use lpc1347::Interrupt;
+
+/// Specify a match register
+#[derive(Copy, Clone)]
+pub enum MatchReg {
+    /// Specifies match register 0
+    Reg0 = 0,
+    /// Specifies match register 1
+    Reg1 = 1,
+    /// Specifies match register 2
+    Reg2 = 2,
+    /// Specifies match register 3
+    Reg3 = 3,
+}
+
+/// Specify PWM-mode
+#[derive(Copy, Clone)]
+pub enum Control {
+    /// Don't do anything
+    Nothing = 0x0,
+    /// Clear the output
+    Clear = 0x1,
+    /// Set the output
+    Set = 0x2,
+    /// Invert the output
+    Toggle = 0x3,
+}
+
+/// Specify a capture register
+#[derive(Copy, Clone)]
+pub enum CaptureReg {
+    /// Capture register 0, fed by the CAP0 pin
+    Cap0 = 0,
+    /// Capture register 1, fed by the CAP1 pin
+    Cap1 = 1,
+}
+
+/// Edges of a capture input
+#[derive(Copy, Clone)]
+pub enum Edge {
+    /// Rising edges
+    Rising,
+    /// Falling edges
+    Falling,
+    /// Both rising and falling edges
+    Both,
+}
+
+/// Registers of a counter/timer, see `Instance`
+#[derive(Copy, Clone)]
+pub enum Register {
+    /// Interrupt register
+    Ir,
+    /// Timer control register
+    Tcr,
+    /// Timer counter
+    Tc,
+    /// Prescale register
+    Pr,
+    /// Prescale counter
+    Pc,
+    /// Match control register
+    Mcr,
+    /// Match register 0-3
+    Mr(MatchReg),
+    /// Capture control register
+    Ccr,
+    /// Capture register 0-1
+    Cr(CaptureReg),
+    /// External match register
+    Emr,
+    /// Count control register
+    Ctcr,
+    /// PWM control register
+    Pwmc,
+}
+
+/// Width of the counter of a timer
+pub trait Width: Copy {
+    /// The largest count
+    const MAX: Self;
+
+    /// Truncate a register value to the counter width
+    fn from_bits(bits: u32) -> Self;
+
+    /// Widen the count to a register value
+    fn into_bits(self) -> u32;
+}
+
+impl Width for u16 {
+    const MAX: u16 = 0xFFFF;
+
+    fn from_bits(bits: u32) -> u16 {
+        bits as u16
+    }
+
+    fn into_bits(self) -> u32 {
+        u32::from(self)
+    }
+}
+
+impl Width for u32 {
+    const MAX: u32 = 0xFFFF_FFFF;
+
+    fn from_bits(bits: u32) -> u32 {
+        bits
+    }
+
+    fn into_bits(self) -> u32 {
+        self
+    }
+}
+
+/// One of the four counter/timers
+///
+/// This is the low-level register access that `Timer` is built on, it is
+/// implemented for `CT16B0`, `CT16B1`, `CT32B0` and `CT32B1`.
+pub trait Instance {
+    /// The width of the counter, `u16` or `u32`
+    type Count: Width;
+
+    /// The NVIC line of the timer
+    const INTERRUPT: Interrupt;
+
+    /// Enable the clock of the timer
+    fn power_up(syscon: &lpc1347::SYSCON);
+
+    /// Read a register
+    fn read(&self, register: Register) -> u32;
+
+    /// Write a register
+    fn write(&self, register: Register, value: u32);
+
+    /// Read-modify-write the bits of a register selected by `mask`
+    fn modify(&self, register: Register, mask: u32, value: u32) {
+        let bits = self.read(register);
+        self.write(register, (bits & !mask) | (value & mask));
+    }
+}
+
+/// Implements `Instance` for a timer peripheral
+macro_rules! instances {
+    ($($CT: ident: ($count: ty, $clock: ident),)+) => {
+        $(
+            impl Instance for lpc1347::$CT {
+                type Count = $count;
+
+                const INTERRUPT: Interrupt = Interrupt::$CT;
+
+                fn power_up(syscon: &lpc1347::SYSCON) {
+                    syscon.sysahbclkctrl.modify(|_, w| w.$clock().bit(true));
+                }
+
+                fn read(&self, register: Register) -> u32 {
+                    match register {
+                        Register::Ir => self.ir.read().bits(),
+                        Register::Tcr => self.tcr.read().bits(),
+                        Register::Tc => self.tc.read().bits(),
+                        Register::Pr => self.pr.read().bits(),
+                        Register::Pc => self.pc.read().bits(),
+                        Register::Mcr => self.mcr.read().bits(),
+                        Register::Mr(mr) => self.mr[mr as usize].read().bits(),
+                        Register::Ccr => self.ccr.read().bits(),
+                        Register::Cr(cap) => self.cr[cap as usize].read().bits(),
+                        Register::Emr => self.emr.read().bits(),
+                        Register::Ctcr => self.ctcr.read().bits(),
+                        Register::Pwmc => self.pwmc.read().bits(),
+                    }
+                }
+
+                fn write(&self, register: Register, value: u32) {
+                    unsafe {
+                        match register {
+                            Register::Ir => self.ir.write(|w| w.bits(value)),
+                            Register::Tcr => self.tcr.write(|w| w.bits(value)),
+                            Register::Tc => self.tc.write(|w| w.bits(value)),
+                            Register::Pr => self.pr.write(|w| w.bits(value)),
+                            Register::Pc => self.pc.write(|w| w.bits(value)),
+                            Register::Mcr => self.mcr.write(|w| w.bits(value)),
+                            Register::Mr(mr) => self.mr[mr as usize].write(|w| w.bits(value)),
+                            Register::Ccr => self.ccr.write(|w| w.bits(value)),
+                            Register::Cr(cap) => self.cr[cap as usize].write(|w| w.bits(value)),
+                            Register::Emr => self.emr.write(|w| w.bits(value)),
+                            Register::Ctcr => self.ctcr.write(|w| w.bits(value)),
+                            Register::Pwmc => self.pwmc.write(|w| w.bits(value)),
+                        }
+                    }
+                }
+            }
+        )+
+    };
+}
+
+instances! {
+    CT16B0: (u16, ct16b0),
+    CT16B1: (u16, ct16b1),
+    CT32B0: (u32, ct32b0),
+    CT32B1: (u32, ct32b1),
+}
+
+/// Counter enable bit of TCR
+const TCR_CEN: u32 = 1 << 0;
+
+/// Counter reset bit of TCR
+const TCR_CRST: u32 = 1 << 1;
+
+/// Interrupt flag of capture register 0 in IR, followed by capture register 1
+const IR_CR0INT: u32 = 1 << 4;
+
+/// Counter/timer mode field of CTCR
+const CTCR_CTM: u32 = 0x3;
+
+/// Count input select field of CTCR
+const CTCR_CIS: u32 = 0x3 << 2;
+
+/// Clear-on-capture enable bit of CTCR
+const CTCR_ENCC: u32 = 1 << 4;
+
+/// Clear-on-capture edge select field of CTCR
+const CTCR_SELCC: u32 = 0x7 << 5;
+
+/// Driver for any of the 16-bit and 32-bit counter/timers
+///
+/// # Example
+/// ```
+/// // Interrupt every second from a 12 MHz peripheral clock
+/// let mut timer = Timer::new(p.device.CT32B0, &p.device.SYSCON);
+/// timer.set_prescaler(11_999);
+/// timer.set_match(MatchReg::Reg0, 1000);
+/// timer.set_interrupt(MatchReg::Reg0, true, true, false);
+/// timer.listen(&mut p.core.NVIC);
+/// timer.set_enabled(true);
+/// ```
+pub struct Timer<T> {
+    timer: T,
+}
+
+impl<T: Instance> Timer<T> {
+    /// Take ownership of the timer and enable its clock
+    ///
+    /// The timer is left stopped, with its interrupt disabled in the NVIC.
+    pub fn new(timer: T, syscon: &lpc1347::SYSCON) -> Self {
+        T::power_up(syscon);
+        Timer { timer }
+    }
+
+    /// Release the timer
+    pub fn free(self) -> T {
+        self.timer
+    }
+
+    /// Start or stop counting
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.timer
+            .modify(Register::Tcr, TCR_CEN, if enabled { TCR_CEN } else { 0 });
+    }
+
+    /// Determine if the timer is counting
+    pub fn is_enabled(&self) -> bool {
+        self.timer.read(Register::Tcr) & TCR_CEN != 0
+    }
+
+    /// Reset the timer and prescale counters to zero
+    pub fn reset(&mut self) {
+        // The counters stay in reset while CRST is set
+        self.timer.modify(Register::Tcr, TCR_CRST, TCR_CRST);
+        self.timer.modify(Register::Tcr, TCR_CRST, 0);
+    }
+
+    /// Get the current count
+    pub fn get_counter(&self) -> T::Count {
+        T::Count::from_bits(self.timer.read(Register::Tc))
+    }
+
+    /// Set the prescaler, the timer counts once every `value + 1` clock cycles
+    pub fn set_prescaler(&mut self, value: T::Count) {
+        self.timer.write(Register::Pr, value.into_bits());
+    }
+
+    /// Get the prescaler
+    pub fn get_prescaler(&self) -> T::Count {
+        T::Count::from_bits(self.timer.read(Register::Pr))
+    }
+
+    /// Set a match-register value
+    ///
+    /// # Arguments
+    /// * `mr` - Selects the MatchReg
+    /// * `value` - Value to match on
+    pub fn set_match(&mut self, mr: MatchReg, value: T::Count) {
+        self.timer.write(Register::Mr(mr), value.into_bits());
+    }
+
+    /// Get a match-register value
+    pub fn get_match(&self, mr: MatchReg) -> T::Count {
+        T::Count::from_bits(self.timer.read(Register::Mr(mr)))
+    }
+
+    /// Make the counter wrap at a frequency, by resetting it on a match
+    ///
+    /// The prescaler is kept as low as possible, which gives the finest
+    /// resolution. The period is at most the largest count, so that a match
+    /// one past the end of the period still fits. Returns the period in
+    /// counts, or `Error::InvalidFrequency` if the frequency is zero, above
+    /// half the peripheral clock, or too low for the timer width.
+    ///
+    /// # Arguments
+    /// * `mr` - The match register that resets the counter
+    /// * `frequency` - The frequency in Hz
+    /// * `pclk` - The peripheral clock of the timer in Hz
+    pub fn set_frequency(&mut self, mr: MatchReg, frequency: u32, pclk: u32) -> Result<u32, Error> {
+        if frequency == 0 {
+            return Err(Error::InvalidFrequency);
+        }
+
+        let ticks = pclk / frequency;
+        if ticks < 2 {
+            return Err(Error::InvalidFrequency);
+        }
+
+        // Smallest prescaler that keeps the period within the largest count
+        let max = u64::from(T::Count::MAX.into_bits());
+        let prescaler = (u64::from(ticks) - 1) / max;
+        if prescaler > max {
+            return Err(Error::InvalidFrequency);
+        }
+        let prescaler = prescaler as u32;
+        let period = ticks / (prescaler + 1);
+
+        self.set_prescaler(T::Count::from_bits(prescaler));
+        self.set_match(mr, T::Count::from_bits(period - 1));
+
+        // Only set the reset bit, leaving the interrupt and stop bits alone
+        let reset = 0x2 << (3 * mr as u32);
+        self.timer.modify(Register::Mcr, reset, reset);
+
+        Ok(period)
+    }
+
+    /// Configure what happens when a match register matches
+    ///
+    /// # Arguments
+    /// * `mr` - Selects the MatchReg
+    /// * `interrupt` - Enable interrupt when MatchReg matches
+    /// * `reset` - Reset the TC counter when MatchReg matches
+    /// * `stop` - Stop the counter and disable the timer when MatchReg matches
+    pub fn set_interrupt(&mut self, mr: MatchReg, interrupt: bool, reset: bool, stop: bool) {
+        let shift = 3 * mr as u32;
+        let value = (interrupt as u32) | (reset as u32) << 1 | (stop as u32) << 2;
+        self.timer
+            .modify(Register::Mcr, 0x7 << shift, value << shift);
+    }
+
+    /// Determine if a match interrupt is pending
+    pub fn is_pending(&self, mr: MatchReg) -> bool {
+        self.timer.read(Register::Ir) & (1 << mr as u32) != 0
+    }
+
+    /// Clear a match interrupt
+    pub fn clear_interrupt(&mut self, mr: MatchReg) {
+        self.timer.write(Register::Ir, 1 << mr as u32);
+    }
+
+    /// Enable the timer interrupt in the NVIC
+    pub fn listen(&mut self, nvic: &mut lpc1347::NVIC) {
+        nvic.enable(T::INTERRUPT);
+    }
+
+    /// Disable the timer interrupt in the NVIC
+    pub fn unlisten(&mut self, nvic: &mut lpc1347::NVIC) {
+        nvic.disable(T::INTERRUPT);
+    }
+
+    /// Configure the external match output of a match register
+    ///
+    /// # Arguments
+    /// * `mr` - Select match-register
+    /// * `control` - Select behaviour when a match occurs
+    /// * `mat` - The current value of the output
+    pub fn set_external_match(&mut self, mr: MatchReg, control: Control, mat: bool) {
+        let shift = mr as u32;
+        self.timer.modify(
+            Register::Emr,
+            (1 << shift) | (0x3 << (4 + 2 * shift)),
+            (mat as u32) << shift | (control as u32) << (4 + 2 * shift),
+        );
+    }
+
+    /// Determine the current value of an external match output
+    pub fn get_external_match(&self, mr: MatchReg) -> bool {
+        self.timer.read(Register::Emr) & (1 << mr as u32) != 0
+    }
+
+    /// Enable or disable PWM mode for the output of a match register
+    ///
+    /// In PWM mode the output is low until the counter reaches the match
+    /// value, and high from there until the counter is reset. The period is
+    /// typically set with another match register that resets the counter.
+    pub fn set_pwm(&mut self, mr: MatchReg, enabled: bool) {
+        let bit = 1 << mr as u32;
+        self.timer
+            .modify(Register::Pwmc, bit, if enabled { bit } else { 0 });
+    }
+
+    /// Hand a pin over to the output of a match register
+    ///
+    /// The pin has been routed to the output by `into_match_output`, the match
+    /// register that drives the pin is given by `P::MATCH`. Most outputs are
+    /// available on a primary pin and an alternate pin on port 1.
+    ///
+    /// # Example
+    /// ```
+    /// // CT32B0_MAT0 on its alternate pin
+    /// let mut timer = Timer::new(p.device.CT32B0, &p.device.SYSCON);
+    /// timer.set_match_pin(pins.pio1_24.into_match_output());
+    /// ```
+    pub fn set_match_pin<P: MatchPin<T>>(&mut self, _pin: P) {}
+
+    /// Configure a capture register
+    ///
+    /// The capture register loads the timer counter on the selected edges of
+    /// its CAP pin, see `set_capture_pin`.
+    ///
+    /// # Arguments
+    /// * `cap` - Selects the CaptureReg
+    /// * `edge` - Capture on rising, falling or both edges
+    /// * `interrupt` - Enable interrupt on capture
+    /// * `reset` - Reset the TC counter on capture, which is not possible on
+    ///   both edges and returns `Error::InvalidTrigger`
+    ///
+    /// # Example
+    /// ```
+    /// // Measure the period of the signal on pio1_16
+    /// timer.set_capture_pin(pins.pio1_16.into_capture());
+    /// timer.set_capture(CaptureReg::Cap0, Edge::Rising, true, true).unwrap();
+    /// timer.set_enabled(true);
+    /// ```
+    pub fn set_capture(
+        &mut self,
+        cap: CaptureReg,
+        edge: Edge,
+        interrupt: bool,
+        reset: bool,
+    ) -> Result<(), Error> {
+        if let (Edge::Both, true) = (edge, reset) {
+            return Err(Error::InvalidTrigger);
+        }
+
+        let edges = match edge {
+            Edge::Rising => 0x1,
+            Edge::Falling => 0x2,
+            Edge::Both => 0x3,
+        };
+
+        let shift = 3 * cap as u32;
+        self.timer.modify(
+            Register::Ccr,
+            0x7 << shift,
+            (edges | (interrupt as u32) << 2) << shift,
+        );
+
+        if reset {
+            self.set_clear_on_capture(Some((cap, edge)))?;
+        } else if self.clears_on(cap) {
+            self.set_clear_on_capture(None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Stop capturing, and stop resetting the counter, on a capture register
+    pub fn disable_capture(&mut self, cap: CaptureReg) {
+        self.timer.modify(Register::Ccr, 0x7 << (3 * cap as u32), 0);
+
+        if self.clears_on(cap) {
+            self.timer.modify(Register::Ctcr, CTCR_ENCC, 0);
+        }
+    }
+
+    /// Count edges on a capture input instead of clock cycles
+    ///
+    /// The counter then works as a pulse counter for the CAP pin, see
+    /// `set_capture_pin`. The capture register of the input can not be used
+    /// for capturing at the same time and is disabled. The input signal must
+    /// stay below half the peripheral clock rate.
+    ///
+    /// # Arguments
+    /// * `cap` - Selects the input to count on
+    /// * `edge` - Count rising, falling or both edges
+    ///
+    /// # Example
+    /// ```
+    /// // Count the pulses of a flow meter on pio1_28
+    /// timer.set_capture_pin(pins.pio1_28.into_capture());
+    /// timer.set_counter_mode(CaptureReg::Cap0, Edge::Rising);
+    /// timer.set_enabled(true);
+    ///
+    /// let pulses = timer.get_counter();
+    /// ```
+    pub fn set_counter_mode(&mut self, cap: CaptureReg, edge: Edge) {
+        let mode = match edge {
+            Edge::Rising => 0x1,
+            Edge::Falling => 0x2,
+            Edge::Both => 0x3,
+        };
+
+        // The input must not be used for capturing while counting on it
+        self.timer.modify(Register::Ccr, 0x7 << (3 * cap as u32), 0);
+        self.timer.modify(
+            Register::Ctcr,
+            CTCR_CTM | CTCR_CIS,
+            mode | (cap as u32) << 2,
+        );
+    }
+
+    /// Count the peripheral clock (through the prescaler), the default
+    pub fn set_timer_mode(&mut self) {
+        self.timer.modify(Register::Ctcr, CTCR_CTM | CTCR_CIS, 0);
+    }
+
+    /// Reset the counter on an edge of a capture input
+    ///
+    /// In counter mode this restarts the count, e.g. counting the edges on
+    /// CAP0 between two rising edges on CAP1. Only one edge can reset the
+    /// counter, `Edge::Both` returns `Error::InvalidTrigger`.
+    ///
+    /// # Arguments
+    /// * `clear` - The input and edge that resets the counter, or `None`
+    ///
+    /// # Example
+    /// ```
+    /// // Count CAP0 edges, restarting on every rising edge on CAP1
+    /// timer.set_counter_mode(CaptureReg::Cap0, Edge::Rising);
+    /// timer.set_clear_on_capture(Some((CaptureReg::Cap1, Edge::Rising))).unwrap();
+    /// ```
+    pub fn set_clear_on_capture(&mut self, clear: Option<(CaptureReg, Edge)>) -> Result<(), Error> {
+        match clear {
+            Some((_, Edge::Both)) => Err(Error::InvalidTrigger),
+            Some((cap, edge)) => {
+                let falling = if let Edge::Falling = edge { 1 } else { 0 };
+                let selcc = 2 * cap as u32 + falling;
+                self.timer.modify(
+                    Register::Ctcr,
+                    CTCR_ENCC | CTCR_SELCC,
+                    CTCR_ENCC | selcc << 5,
+                );
+                Ok(())
+            }
+            None => {
+                self.timer.modify(Register::Ctcr, CTCR_ENCC, 0);
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the counter value of the latest capture
+    pub fn get_capture(&self, cap: CaptureReg) -> T::Count {
+        T::Count::from_bits(self.timer.read(Register::Cr(cap)))
+    }
+
+    /// Determine if a capture interrupt is pending
+    pub fn is_capture_pending(&self, cap: CaptureReg) -> bool {
+        self.timer.read(Register::Ir) & (IR_CR0INT << cap as u32) != 0
+    }
+
+    /// Clear a capture interrupt
+    pub fn clear_capture_interrupt(&mut self, cap: CaptureReg) {
+        self.timer.write(Register::Ir, IR_CR0INT << cap as u32);
+    }
+
+    /// Hand a pin over to one of the capture inputs of the timer
+    ///
+    /// The pin has been routed to the input by `into_capture`, the capture
+    /// register that it feeds is given by `P::CAPTURE`.
+    pub fn set_capture_pin<P: CapturePin<T>>(&mut self, _pin: P) {}
+
+    /// Determine if the counter is reset by a capture register
+    fn clears_on(&self, cap: CaptureReg) -> bool {
+        let ctcr = self.timer.read(Register::Ctcr);
+        ctcr & CTCR_ENCC != 0 && (ctcr & CTCR_SELCC) >> 6 == cap as u32
+    }
+}
+
+/// A pin that is driven by a match output of the timer `T`
+///
+/// Implemented for the pins of `gpio` in `MatchOutput` mode, which are
+/// obtained through `into_match_output`.
+pub trait MatchPin<T> {
+    /// The match register that drives the pin
+    const MATCH: MatchReg;
+}
+
+/// Implements `MatchPin` for the pins of each match output
+macro_rules! match_pins {
+    ($($CT: ident: [$($PIOX: ident: $mr: ident),+],)+) => {
+        $(
+            $(
+                impl MatchPin<lpc1347::$CT> for gpio::$PIOX<gpio::MatchOutput> {
+                    const MATCH: MatchReg = MatchReg::$mr;
+                }
+            )+
+        )+
+    };
+}
+
+match_pins! {
+    CT16B0: [
+        Pio0_8: Reg0,
+        Pio1_13: Reg0,
+        Pio0_9: Reg1,
+        Pio1_14: Reg1,
+        Pio0_10: Reg2,
+        Pio1_15: Reg2
+    ],
+    CT16B1: [
+        Pio0_21: Reg0,
+        Pio0_22: Reg1,
+        Pio1_23: Reg1
+    ],
+    CT32B0: [
+        Pio0_18: Reg0,
+        Pio1_24: Reg0,
+        Pio0_19: Reg1,
+        Pio1_25: Reg1,
+        Pio0_1: Reg2,
+        Pio1_26: Reg2,
+        Pio0_11: Reg3,
+        Pio1_27: Reg3
+    ],
+    CT32B1: [
+        Pio0_13: Reg0,
+        Pio1_0: Reg0,
+        Pio0_14: Reg1,
+        Pio1_1: Reg1,
+        Pio0_15: Reg2,
+        Pio1_2: Reg2,
+        Pio0_16: Reg3,
+        Pio1_3: Reg3
+    ],
+}
+
+/// A pin that feeds a capture input of the timer `T`
+///
+/// Implemented for the pins of `gpio` in `Capture` mode, which are obtained
+/// through `into_capture`.
+pub trait CapturePin<T> {
+    /// The capture register fed by the pin
+    const CAPTURE: CaptureReg;
+}
+
+/// Implements `CapturePin` for the pins of each capture input
+macro_rules! capture_pins {
+    ($($CT: ident: [$($PIOX: ident: $cap: ident),+],)+) => {
+        $(
+            $(
+                impl CapturePin<lpc1347::$CT> for gpio::$PIOX<gpio::Capture> {
+                    const CAPTURE: CaptureReg = CaptureReg::$cap;
+                }
+            )+
+        )+
+    };
+}
+
+capture_pins! {
+    CT16B0: [
+        Pio0_2: Cap0,
+        Pio1_16: Cap0,
+        Pio1_17: Cap1
+    ],
+    CT16B1: [
+        Pio0_20: Cap0,
+        Pio1_18: Cap1
+    ],
+    CT32B0: [
+        Pio0_17: Cap0,
+        Pio1_28: Cap0,
+        Pio1_29: Cap1
+    ],
+    CT32B1: [
+        Pio0_12: Cap0,
+        Pio1_4: Cap0,
+        Pio1_5: Cap1
+    ],
+}
+
+/// `CountDown` counts on MR0, while `Pwm` keeps the period in MR3 and uses
+/// MR0-MR2 as channels, so use the timer for one or the other. `Pwm` ignores
+/// MR3 as a channel, and a period of MR3 + 1 ticks is the maximum duty.
+///
+/// # Example
+/// ```
+/// // 1 kHz ticks from a 12 MHz peripheral clock
+/// let mut timer = Timer::new(p.device.CT32B0, &p.device.SYSCON);
+/// timer.set_prescaler(11_999);
+/// timer.start(500u32);
+/// block!(timer.wait()).unwrap();
+/// ```
+#[cfg(feature = "hal")]
+impl<T: Instance> ::embedded_hal::timer::CountDown for Timer<T> {
+    type Time = T::Count;
+
+    fn start<C>(&mut self, count: C)
+    where
+        C: Into<T::Count>,
+    {
+        self.set_enabled(false);
+        self.reset();
+        self.set_match(MatchReg::Reg0, count.into());
+        self.set_interrupt(MatchReg::Reg0, true, true, false);
+        self.clear_interrupt(MatchReg::Reg0);
+        self.set_enabled(true);
+    }
+
+    fn wait(&mut self) -> ::nb::Result<(), ::void::Void> {
+        if self.is_pending(MatchReg::Reg0) {
+            self.clear_interrupt(MatchReg::Reg0);
+            Ok(())
+        } else {
+            Err(::nb::Error::WouldBlock)
+        }
+    }
+}
+
+#[cfg(feature = "hal")]
+impl<T: Instance> ::embedded_hal::timer::Periodic for Timer<T> {}
+
+#[cfg(feature = "hal")]
+impl<T: Instance> Timer<T> {
+    /// The PWM period in ticks, MR3 + 1, limited to the width of the counter
+    fn pwm_ticks(&self) -> u32 {
+        let period = self.get_match(MatchReg::Reg3).into_bits().saturating_add(1);
+        ::core::cmp::min(period, T::Count::MAX.into_bits())
+    }
+}
+
+#[cfg(feature = "hal")]
+impl<T: Instance> ::embedded_hal::Pwm for Timer<T> {
+    type Channel = MatchReg;
+    type Time = T::Count;
+    type Duty = T::Count;
+
+    fn disable(&mut self, channel: MatchReg) {
+        if let MatchReg::Reg3 = channel {
+            return;
+        }
+        self.set_pwm(channel, false);
+    }
+
+    fn enable(&mut self, channel: MatchReg) {
+        if let MatchReg::Reg3 = channel {
+            return;
+        }
+        self.set_pwm(channel, true);
+        self.set_enabled(true);
+    }
+
+    fn get_period(&self) -> T::Count {
+        self.get_match(MatchReg::Reg3)
+    }
+
+    fn get_duty(&self, channel: MatchReg) -> T::Count {
+        if let MatchReg::Reg3 = channel {
+            return T::Count::from_bits(0);
+        }
+
+        // The output goes high on match and low again when MR3 resets the counter
+        let value = self.get_match(channel).into_bits();
+        T::Count::from_bits(self.pwm_ticks().saturating_sub(value))
+    }
+
+    fn get_max_duty(&self) -> T::Count {
+        T::Count::from_bits(self.pwm_ticks())
+    }
+
+    fn set_duty(&mut self, channel: MatchReg, duty: T::Count) {
+        if let MatchReg::Reg3 = channel {
+            return;
+        }
+
+        // A match of MR3 would still give a pulse of one tick, MR3 + 1 never matches
+        let period = self.pwm_ticks();
+        let duty = ::core::cmp::min(duty.into_bits(), period);
+        self.set_match(channel, T::Count::from_bits(period - duty));
+    }
+
+    fn set_period<P>(&mut self, period: P)
+    where
+        P: Into<T::Count>,
+    {
+        self.set_match(MatchReg::Reg3, period.into());
+        self.set_interrupt(MatchReg::Reg3, false, true, false);
+    }
+}
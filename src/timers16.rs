@@ -2,7 +2,9 @@
 
 extern crate lpc1347;
 
+use iocon;
 use lpc1347::Interrupt::{CT16B0, CT16B1};
+pub use timer::{Control, MatchReg};
 
 /// Timer selection
 #[derive(Copy, Clone)]
@@ -13,32 +15,6 @@ pub enum Timer {
     Timer1,
 }
 
-/// Specify a match register
-#[derive(Copy, Clone)]
-pub enum MatchReg {
-    /// Specifies match register 0
-    Reg0 = 0,
-    /// Specifies match register 1
-    Reg1 = 1,
-    /// Specifies match register 2
-    Reg2 = 2,
-    /// Specifies match register 3
-    Reg3 = 3,
-}
-
-/// Specify PWM-mode
-#[derive(Copy, Clone)]
-pub enum Control {
-    /// Don't do anything
-    Nothing = 0x0,
-    /// Clear the output
-    Clear = 0x1,
-    /// Set the output
-    Set = 0x2,
-    /// Invert the output
-    Toggle = 0x3,
-}
-
 /// Initialize 16-bit timers
 ///
 /// # Arguments
@@ -330,6 +306,11 @@ pub fn reset_t1(ct16b1: &lpc1347::CT16B1) {
 
 /// Configure pins to use for PWM output
 ///
+/// Only the primary pin of each output is used, see `timer::MatchPin` for
+/// the alternate pins.
+///
+/// CT16B0 MR3 and CT16B1 MR2/MR3 have no output pin, selecting them does nothing.
+///
 /// # Arguments
 /// * `timer` - Selects one of the two 16-bit timers
 /// * `mr` - Match register to activate GPIO pins for
@@ -342,40 +323,34 @@ pub fn reset_t1(ct16b1: &lpc1347::CT16B1) {
 pub unsafe fn set_pwm_output_pin(iocon: &lpc1347::IOCON, timer: Timer, mr: MatchReg) {
     match timer {
         Timer::Timer0 => match mr {
-            MatchReg::Reg0 => {
-                iocon.pio0_18.modify(|_, w| w.func().bits(0x2));
-                iocon.pio0_18.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg1 => {
-                iocon.pio0_19.modify(|_, w| w.func().bits(0x2));
-                iocon.pio0_19.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg2 => {
-                iocon.pio0_1.modify(|_, w| w.func().bits(0x2));
-                iocon.pio0_1.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg3 => {
-                iocon.tdi_pio0_11.modify(|_, w| w.func().bits(0x3));
-                iocon.tdi_pio0_11.modify(|_, w| w.mode().bits(0x2));
-            }
+            MatchReg::Reg0 => iocon::Pio0_8::config()
+                .function(iocon::Ct16b0Mat0)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            MatchReg::Reg1 => iocon::Pio0_9::config()
+                .function(iocon::Ct16b0Mat1)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            MatchReg::Reg2 => iocon::Pio0_10::config()
+                .function(iocon::Ct16b0Mat2)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            // Not connected to any pin
+            MatchReg::Reg3 => {}
         },
         Timer::Timer1 => match mr {
-            MatchReg::Reg0 => {
-                iocon.tdo_pio0_13.modify(|_, w| w.func().bits(0x3));
-                iocon.tdo_pio0_13.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg1 => {
-                iocon.trst_pio0_14.modify(|_, w| w.func().bits(0x3));
-                iocon.trst_pio0_14.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg2 => {
-                iocon.swdio_pio0_15.modify(|_, w| w.func().bits(0x3));
-                iocon.swdio_pio0_15.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg3 => {
-                iocon.pio0_16.modify(|_, w| w.func().bits(0x2));
-                iocon.pio0_16.modify(|_, w| w.mode().bits(0x2));
-            }
+            MatchReg::Reg0 => iocon::Pio0_21::config()
+                .function(iocon::Ct16b1Mat0)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            MatchReg::Reg1 => iocon::Pio0_22::config()
+                .function(iocon::Ct16b1Mat1)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            // Not connected to any pin
+            MatchReg::Reg2 => {}
+            // Not connected to any pin
+            MatchReg::Reg3 => {}
         },
     }
 }
@@ -1,7 +1,9 @@
 #![allow(dead_code)]
 extern crate lpc1347;
 
+use iocon;
 use lpc1347::Interrupt::{CT32B0, CT32B1};
+pub use timer::{Control, MatchReg};
 
 /// Specify which 32b-timer to use
 #[derive(Copy, Clone)]
@@ -12,32 +14,6 @@ pub enum Timer {
     Timer1,
 }
 
-/// Specify match registers to use
-#[derive(Copy, Clone)]
-pub enum MatchReg {
-    /// MR0
-    Reg0,
-    /// MR1
-    Reg1,
-    /// MR2
-    Reg2,
-    /// MR3
-    Reg3,
-}
-
-/// Specify PWM-mode
-#[derive(Copy, Clone)]
-pub enum Control {
-    /// Don't do anything
-    Nothing = 0x0,
-    /// Clear the output
-    Clear = 0x1,
-    /// Set the output
-    Set = 0x2,
-    /// Invert the output
-    Toggle = 0x3,
-}
-
 
 /// Initialize 32-bit timers
 ///
@@ -330,6 +306,9 @@ pub fn reset_t1(ct32b1: &lpc1347::CT32B1) {
 
 /// Configure pins to use for PWM output
 ///
+/// Only the primary pin of each output is used, see `timer::MatchPin` for
+/// the alternate pins.
+///
 /// # Arguments
 /// * `timer` - Selects one of the two 32-bit timers
 /// * `mr` - Match register to activate GPIO pins for
@@ -342,40 +321,40 @@ pub fn reset_t1(ct32b1: &lpc1347::CT32B1) {
 pub unsafe fn set_pwm_output_pin(iocon: &lpc1347::IOCON, timer: Timer, mr: MatchReg) {
     match timer {
         Timer::Timer0 => match mr {
-            MatchReg::Reg0 => {
-                iocon.pio0_18.modify(|_, w| w.func().bits(0x2));
-                iocon.pio0_18.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg1 => {
-                iocon.pio0_19.modify(|_, w| w.func().bits(0x2));
-                iocon.pio0_19.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg2 => {
-                iocon.pio0_1.modify(|_, w| w.func().bits(0x2));
-                iocon.pio0_1.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg3 => {
-                iocon.tdi_pio0_11.modify(|_, w| w.func().bits(0x3));
-                iocon.tdi_pio0_11.modify(|_, w| w.mode().bits(0x2));
-            }
+            MatchReg::Reg0 => iocon::Pio0_18::config()
+                .function(iocon::Ct32b0Mat0)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            MatchReg::Reg1 => iocon::Pio0_19::config()
+                .function(iocon::Ct32b0Mat1)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            MatchReg::Reg2 => iocon::Pio0_1::config()
+                .function(iocon::Ct32b0Mat2)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            MatchReg::Reg3 => iocon::Pio0_11::config()
+                .function(iocon::Ct32b0Mat3)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
         },
         Timer::Timer1 => match mr {
-            MatchReg::Reg0 => {
-                iocon.tdo_pio0_13.modify(|_, w| w.func().bits(0x3));
-                iocon.tdo_pio0_13.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg1 => {
-                iocon.trst_pio0_14.modify(|_, w| w.func().bits(0x3));
-                iocon.trst_pio0_14.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg2 => {
-                iocon.swdio_pio0_15.modify(|_, w| w.func().bits(0x3));
-                iocon.swdio_pio0_15.modify(|_, w| w.mode().bits(0x2));
-            }
-            MatchReg::Reg3 => {
-                iocon.pio0_16.modify(|_, w| w.func().bits(0x2));
-                iocon.pio0_16.modify(|_, w| w.mode().bits(0x2));
-            }
+            MatchReg::Reg0 => iocon::Pio0_13::config()
+                .function(iocon::Ct32b1Mat0)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            MatchReg::Reg1 => iocon::Pio0_14::config()
+                .function(iocon::Ct32b1Mat1)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            MatchReg::Reg2 => iocon::Pio0_15::config()
+                .function(iocon::Ct32b1Mat2)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
+            MatchReg::Reg3 => iocon::Pio0_16::config()
+                .function(iocon::Ct32b1Mat3)
+                .mode(iocon::Mode::PullUp)
+                .apply(iocon),
         },
     }
 }
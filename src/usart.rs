@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 extern crate lpc1347;
+use iocon;
 use lpc1347::Interrupt::USART;
 
 /// The length of the USART RX buffer
@@ -61,21 +62,13 @@ pub fn init(
     nvic.disable(USART);
     init_buffer(pcb);
 
-    unsafe {
-        // RXD
-        iocon.pio0_18.modify(|_, w| w.func().bits(0x1));
-        // TXD
-        iocon.pio0_19.modify(|_, w| w.func().bits(0x1));
-    }
+    iocon::Pio0_18::config().function(iocon::Rxd).apply(iocon);
+    iocon::Pio0_19::config().function(iocon::Txd).apply(iocon);
 
     // Setup flowcontrol (RTS/CTS)
     if flow_control {
-        unsafe {
-            // CTS
-            iocon.pio0_7.modify(|_, w| w.func().bits(0x1));
-            // RTS
-            iocon.pio0_17.modify(|_, w| w.func().bits(0x1));
-        }
+        iocon::Pio0_7::config().function(iocon::Cts).apply(iocon);
+        iocon::Pio0_17::config().function(iocon::Rts).apply(iocon);
     }
 
     // Start USART clock
@@ -245,3 +238,108 @@ pub fn clear_fifo(pcb: &mut Pcb) {
 pub fn data_pending(pcb: &Pcb) -> bool {
     pcb.rxfifo.length > 0
 }
+
+/// Receive errors reported through the embedded-hal serial traits
+#[cfg(feature = "hal")]
+#[derive(Copy, Clone, Debug)]
+pub enum Error {
+    /// A byte was lost because the RX FIFO was full
+    Overrun,
+    /// Parity check failed
+    Parity,
+    /// No valid stop bit was received
+    Framing,
+    /// The RX line was held low for a full character
+    Break,
+}
+
+/// Owned USART for use through the embedded-hal serial traits
+///
+/// The USART must be set up through `init` first. `init` enables the receive
+/// interrupts, so either keep an `USART` task around or disable the interrupt
+/// in the NVIC if the port is only polled.
+///
+/// # Example
+/// ```
+/// usart::init(&mut pcb, &mut p.core.NVIC, &p.device.IOCON, &p.device.SYSCON,
+///             &p.device.USART, 9600, false);
+/// p.core.NVIC.disable(lpc1347::Interrupt::USART);
+///
+/// let mut serial = usart::Serial::new(p.device.USART);
+/// block!(serial.write(b'x')).unwrap();
+/// ```
+#[cfg(feature = "hal")]
+pub struct Serial {
+    usart: lpc1347::USART,
+}
+
+#[cfg(feature = "hal")]
+impl Serial {
+    /// Take ownership of an initialized USART
+    pub fn new(usart: lpc1347::USART) -> Self {
+        Serial { usart }
+    }
+
+    /// Release the USART
+    pub fn free(self) -> lpc1347::USART {
+        self.usart
+    }
+}
+
+#[cfg(feature = "hal")]
+impl ::embedded_hal::serial::Read<u8> for Serial {
+    type Error = Error;
+
+    fn read(&mut self) -> ::nb::Result<u8, Error> {
+        let lsr = self.usart.lsr.read();
+
+        // Reading LSR clears the error flags, the faulty byte is discarded below
+        let error = if lsr.pe().bit() {
+            Some(Error::Parity)
+        } else if lsr.fe().bit() {
+            Some(Error::Framing)
+        } else if lsr.bi().bit() {
+            Some(Error::Break)
+        } else {
+            None
+        };
+
+        if let Some(error) = error {
+            unsafe {
+                let _dummy = self.usart.dll.rbr.read();
+            }
+            Err(::nb::Error::Other(error))
+        } else if lsr.oe().bit() {
+            // The byte in RBR is valid, the lost one came after it
+            Err(::nb::Error::Other(Error::Overrun))
+        } else if lsr.rdr().bit() {
+            unsafe { Ok(self.usart.dll.rbr.read().bits() as u8) }
+        } else {
+            Err(::nb::Error::WouldBlock)
+        }
+    }
+}
+
+#[cfg(feature = "hal")]
+impl ::embedded_hal::serial::Write<u8> for Serial {
+    type Error = ::void::Void;
+
+    fn write(&mut self, byte: u8) -> ::nb::Result<(), Self::Error> {
+        if self.usart.lsr.read().thre().bit() {
+            unsafe {
+                self.usart.dll.thr.write(|w| w.thr().bits(byte));
+            }
+            Ok(())
+        } else {
+            Err(::nb::Error::WouldBlock)
+        }
+    }
+
+    fn flush(&mut self) -> ::nb::Result<(), Self::Error> {
+        if self.usart.lsr.read().temt().bit() {
+            Ok(())
+        } else {
+            Err(::nb::Error::WouldBlock)
+        }
+    }
+}
@@ -0,0 +1,294 @@
+#![allow(dead_code)]
+extern crate lpc1347;
+
+use core::time::Duration;
+use error::Error;
+use lpc1347::Interrupt;
+use monotonic::{Instant, Monotonic, Source};
+
+/// What to do when a software timer expires
+#[derive(Copy, Clone)]
+pub enum Handler {
+    /// Call a function from the timer interrupt
+    Call(fn()),
+    /// Pend an interrupt, which runs the RTFM task bound to it
+    Pend(Interrupt),
+}
+
+/// Identifies a scheduled software timer, for cancellation
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimerId(u32);
+
+/// A scheduled software timer, see `Wheel::new` for the storage
+#[derive(Copy, Clone)]
+pub struct Entry {
+    id: TimerId,
+    deadline: Instant,
+    period: Option<Duration>,
+    handler: Handler,
+}
+
+/// Software timers multiplexed on the alarm of a `Monotonic` clock
+///
+/// The timers are kept sorted by deadline, and the nearest deadline is
+/// programmed into the alarm. `on_interrupt` takes the place of
+/// `Monotonic::on_interrupt` in the timer interrupt, and runs the handlers of
+/// the expired timers.
+///
+/// # Example
+/// ```
+/// // Storage, e.g. a resource
+/// static mut TIMERS: [Option<wheel::Entry>; 16] = [None; 16];
+///
+/// // In init
+/// let timer = Timer::new(p.device.CT32B0, &p.device.SYSCON);
+/// let clock = Monotonic::new(timer, clock::get_system_clock(), &mut p.core.NVIC).unwrap();
+/// let mut wheel = Wheel::new(clock, unsafe { &mut TIMERS });
+///
+/// // Run the USART task in 5 ms, and the blink task every 500 ms
+/// let id = wheel
+///     .schedule(Duration::from_millis(5), Handler::Pend(Interrupt::USART))
+///     .unwrap();
+/// wheel
+///     .schedule_periodic(Duration::from_millis(500), Handler::Pend(Interrupt::SSP0))
+///     .unwrap();
+///
+/// // When the reply arrives in time
+/// wheel.cancel(id);
+///
+/// // In the CT32B0 task, which owns `wheel`
+/// r.WHEEL.on_interrupt(&mut r.NVIC);
+/// ```
+pub struct Wheel<'a, T> {
+    clock: Monotonic<T>,
+    entries: &'a mut [Option<Entry>],
+    len: usize,
+    next_id: u32,
+}
+
+impl<'a, T: Source> Wheel<'a, T> {
+    /// Take over a clock, with room for as many timers as `storage` holds
+    pub fn new(mut clock: Monotonic<T>, storage: &'a mut [Option<Entry>]) -> Self {
+        clock.clear_alarm();
+        for entry in storage.iter_mut() {
+            *entry = None;
+        }
+
+        Wheel {
+            clock,
+            entries: storage,
+            len: 0,
+            next_id: 0,
+        }
+    }
+
+    /// Cancel all timers and release the clock
+    pub fn free(mut self) -> Monotonic<T> {
+        self.clock.clear_alarm();
+        self.clock
+    }
+
+    /// Get the current time of the clock
+    pub fn now(&self) -> Instant {
+        Monotonic::<T>::now()
+    }
+
+    /// Run a handler once, after a delay
+    ///
+    /// Returns `Error::QueueFull` if the storage has no free entry.
+    pub fn schedule(&mut self, delay: Duration, handler: Handler) -> Result<TimerId, Error> {
+        let deadline = self.now() + delay;
+        self.insert(deadline, None, handler)
+    }
+
+    /// Run a handler once, at an instant
+    ///
+    /// A deadline that has passed runs the handler on the next interrupt.
+    pub fn schedule_at(&mut self, deadline: Instant, handler: Handler) -> Result<TimerId, Error> {
+        self.insert(deadline, None, handler)
+    }
+
+    /// Run a handler repeatedly, the first time after one period
+    ///
+    /// Returns `Error::InvalidPeriod` for a period below the 1 µs resolution of
+    /// the clock.
+    pub fn schedule_periodic(
+        &mut self,
+        period: Duration,
+        handler: Handler,
+    ) -> Result<TimerId, Error> {
+        // Instants are whole microseconds, a shorter period would not advance
+        if period < Duration::from_micros(1) {
+            return Err(Error::InvalidPeriod);
+        }
+
+        let deadline = self.now() + period;
+        self.insert(deadline, Some(period), handler)
+    }
+
+    /// Cancel a timer, returns false if it has expired or was cancelled
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        match self.position(id) {
+            Some(index) => {
+                self.remove(index);
+                if index == 0 {
+                    self.program();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Determine if a timer is still scheduled
+    pub fn is_scheduled(&self, id: TimerId) -> bool {
+        self.position(id).is_some()
+    }
+
+    /// Get the number of scheduled timers
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine if no timers are scheduled
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Run the expired timers, call this from the timer interrupt
+    ///
+    /// Periodic timers are scheduled again one period after their previous
+    /// deadline, so they do not drift. A periodic timer that has fallen a
+    /// whole period behind is scheduled one period from now instead, so it
+    /// runs at most once per interrupt.
+    pub fn on_interrupt(&mut self, nvic: &mut lpc1347::NVIC) {
+        self.clock.on_interrupt();
+
+        // Deadlines are compared to a fixed time, so that the loop ends even if
+        // the handlers take longer than a period
+        let now = self.now();
+        while let Some(entry) = self.expired(now) {
+            match entry.handler {
+                Handler::Call(function) => function(),
+                Handler::Pend(interrupt) => nvic.set_pending(interrupt),
+            }
+        }
+    }
+
+    /// Take the next timer expired at `now`, rescheduling it if it is periodic
+    fn expired(&mut self, now: Instant) -> Option<Entry> {
+        let entry = match self.entries[..self.len].first() {
+            Some(&Some(entry)) => entry,
+            _ => {
+                self.clock.clear_alarm();
+                return None;
+            }
+        };
+
+        if entry.deadline > now {
+            self.arm(entry.deadline);
+            return None;
+        }
+
+        self.remove(0);
+        if let Some(period) = entry.period {
+            let next = entry.deadline + period;
+            let deadline = if next > now { next } else { now + period };
+
+            // The entry was just removed, so there is room for it
+            let _ = self.place(Entry { deadline, ..entry });
+        }
+        Some(entry)
+    }
+
+    /// Program the alarm for the nearest deadline
+    fn program(&mut self) {
+        match self.entries[..self.len].first() {
+            Some(&Some(entry)) => self.arm(entry.deadline),
+            _ => self.clock.clear_alarm(),
+        }
+    }
+
+    /// Set the alarm to a deadline, or as soon as possible if it has passed
+    fn arm(&mut self, deadline: Instant) {
+        // The counter has to be behind the alarm once it is set, or it only
+        // goes off after the counter wraps
+        loop {
+            let soonest = self.now() + Duration::from_micros(1);
+            let at = if deadline > soonest {
+                deadline
+            } else {
+                soonest
+            };
+
+            self.clock.set_alarm(at);
+            if at > self.now() {
+                return;
+            }
+        }
+    }
+
+    /// Add a new timer
+    fn insert(
+        &mut self,
+        deadline: Instant,
+        period: Option<Duration>,
+        handler: Handler,
+    ) -> Result<TimerId, Error> {
+        let id = TimerId(self.next_id);
+        let index = self.place(Entry {
+            id,
+            deadline,
+            period,
+            handler,
+        })?;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        if index == 0 {
+            self.program();
+        }
+
+        Ok(id)
+    }
+
+    /// Insert an entry after those with an earlier or equal deadline
+    fn place(&mut self, entry: Entry) -> Result<usize, Error> {
+        if self.len == self.entries.len() {
+            return Err(Error::QueueFull);
+        }
+
+        let mut index = self.len;
+        while index > 0 {
+            match self.entries[index - 1] {
+                Some(previous) if previous.deadline > entry.deadline => {
+                    self.entries[index] = self.entries[index - 1];
+                    index -= 1;
+                }
+                _ => break,
+            }
+        }
+
+        self.entries[index] = Some(entry);
+        self.len += 1;
+        Ok(index)
+    }
+
+    /// Remove the entry at an index, keeping the others in order
+    fn remove(&mut self, index: usize) {
+        for i in index..self.len - 1 {
+            self.entries[i] = self.entries[i + 1];
+        }
+        self.len -= 1;
+        self.entries[self.len] = None;
+    }
+
+    /// Find the index of a timer
+    fn position(&self, id: TimerId) -> Option<usize> {
+        self.entries[..self.len]
+            .iter()
+            .position(|entry| match *entry {
+                Some(entry) => entry.id == id,
+                None => false,
+            })
+    }
+}